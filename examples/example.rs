@@ -43,6 +43,13 @@ impl Display for Department {
     }
 }
 
+// Domain events raised by the form's buttons
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FormAction {
+    Accept,
+    Decline,
+}
+
 struct Form {
     tab_controller: TabController,
     departments: ListView<Department>,
@@ -64,8 +71,8 @@ impl Form {
             people: ListView::new("People", std::iter::empty()),
             name: InputControl::from_label("Name"),
             phone: InputControl::from_label("Phone"),
-            ok: Button::new("OK", Message::Yes),
-            cancel: Button::new("Cancel", Message::No),
+            ok: Button::new("OK", Message::custom(FormAction::Accept)),
+            cancel: Button::new("Cancel", Message::custom(FormAction::Decline)),
             chosen_person: None,
         }
     }
@@ -116,20 +123,29 @@ impl Form {
         }
     }
     fn update_actions(&mut self, msg: &Message) -> Message {
-        match *msg {
-            Message::Yes => {
+        match msg.downcast_ref::<FormAction>() {
+            Some(FormAction::Accept) => {
                 self.chosen_person = Some((self.name.value(), self.phone.value()));
                 Message::Quit
             }
-            Message::No => {
+            Some(FormAction::Decline) => {
                 self.chosen_person = None;
                 Message::Quit
             }
-            _ => Message::Noop,
+            None => Message::Noop,
         }
     }
 }
 
+impl Runner for Form {}
+
+impl RunnerWithOutput for Form {
+    type Output = Option<(String, String)>;
+    fn output(&mut self) -> Self::Output {
+        self.chosen_person.take()
+    }
+}
+
 impl Model for Form {
     fn help(&self) -> Option<String> {
         match self.tab_controller.get_current_index() {
@@ -147,6 +163,11 @@ impl Model for Form {
         self.departments.init()
     }
     fn update(&mut self, msg: Message) -> Message {
+        if let Message::Mouse(_) = msg {
+            // Clicking a control focuses it and then lets it handle the click
+            return self.tab_controller.update(&msg)
+                .and(self.update_controls(msg));
+        }
         self.tab_controller.update(&msg)
             .or_else(|| self.update_actions(&msg))
             .or_else(|| self.update_choosers(&msg))
@@ -194,6 +215,7 @@ impl Model for Form {
             button_areas[0],
             button_areas[1],
         ];
+        self.tab_controller.set_areas(control_areas);
         for ( idx, area, focused ) in self.tab_controller.iter_with_areas(control_areas) {
             macro_rules! draw_model {
                 ( $( ( $idx:literal => $control:ident ) )+ ) => {
@@ -313,17 +335,17 @@ async fn main() -> Result<()> {
         }
     ];
 
+    let mut program = Program::new(RunOptions::new().with_mouse_capture(true))?;
     let mut form = LogViewer::new(Form::new(company));
-    form.run().await?;
-    let form = form.into_model();
-    match form.chosen_person {
+    match program.run_for_output(&mut form).await? {
         Some((name, phone)) => {
-            Popup::show(format!("Chose {name}, who may be reached at {phone}")).await?;
+            Popup::show_in(&mut program, format!("Chose {name}, who may be reached at {phone}")).await?;
         }
         None => {
-            Popup::show("Declined to choose anybody.").await?;
+            Popup::show_in(&mut program, "Declined to choose anybody.").await?;
         }
     }
-    
+    program.close();
+
     Ok(())
 }
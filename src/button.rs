@@ -6,6 +6,8 @@ pub struct Button {
     label: String,
     focus: FocusState,
     on_press: Message,
+    // Where the button was last drawn, for hit-testing clicks
+    area: Rect,
 }
 
 impl Button {
@@ -14,6 +16,7 @@ impl Button {
             label: label.to_owned(),
             focus: Default::default(),
             on_press: msg,
+            area: Rect::default(),
         }
     }
 }
@@ -30,10 +33,12 @@ impl Model for Button {
                     _ => Message::Noop,
                 }
             }
+            Message::Mouse(_) if msg.clicked_in(self.area) => self.on_press.clone(),
             _ => Message::Noop,
         }
     }
     fn view(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.area = area;
         let style = match self.focus {
             FocusState::Blur => &STYLES.blur,
             FocusState::Focus => &STYLES.focus,
@@ -90,6 +95,16 @@ impl ButtonBar {
 
 impl Model for ButtonBar {
     fn update(&mut self, msg: Message) -> Message {
+        if let Message::Mouse(_) = msg {
+            // Clicking a button focuses and presses it
+            return match self.buttons.iter().position(|button| msg.clicked_in(button.area)) {
+                Some(index) => {
+                    self.focus_index = index;
+                    Message::Redraw.and(self.buttons[index].update(msg))
+                }
+                None => Message::Noop,
+            };
+        }
         if let Some(button) = self.buttons.get_mut(self.focus_index) {
             button.update(msg)
         } else {
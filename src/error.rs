@@ -25,6 +25,9 @@ pub enum Error {
 
     #[error("Logging Error ({0:?}): {1}")]
     LoggingError(LoggingErrorKind, String),
+
+    #[error("Panic: {message}\n{backtrace}")]
+    Panic { message: String, backtrace: String },
 }
 
 impl Default for Error {
@@ -33,9 +36,9 @@ impl Default for Error {
     }
 }
 
-impl Into<std::io::Error> for Error {
-    fn into(self) -> std::io::Error {
-        std::io::Error::new(std::io::ErrorKind::Other, self.to_string())
+impl From<Error> for std::io::Error {
+    fn from(err: Error) -> Self {
+        std::io::Error::other(err.to_string())
     }
 }
 
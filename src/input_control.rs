@@ -1,6 +1,6 @@
 use crate::prelude_internal::*;
 use tui_input::backend::crossterm::EventHandler;
-use tui_input::Input;
+use tui_input::{Input, InputRequest};
 
 #[derive(Debug,Default,Clone)]
 pub struct InputControl {
@@ -51,6 +51,15 @@ impl Model for InputControl {
                 self.input.handle_event(&event);
                 Message::Redraw
             }
+            Message::Paste(text) => {
+                // Single line control, so a trailing line break is dropped
+                // and any others become spaces
+                for c in text.trim_end_matches(['\r', '\n']).replace("\r\n", "\n").chars() {
+                    let c = if c == '\n' || c == '\r' { ' ' } else { c };
+                    self.input.handle(InputRequest::InsertChar(c));
+                }
+                Message::Redraw
+            }
             _ => Message::Noop,
         }
     }
@@ -1,9 +1,18 @@
 pub mod error;
 pub mod message;
+pub mod options;
+pub mod shell;
+pub mod tasks;
+pub mod subscription;
+pub mod program;
+pub mod recording;
+pub mod testing;
 pub mod input_control;
 pub mod button;
 pub mod styles;
 pub mod spinner;
+pub mod matcher;
+pub mod list_source;
 pub mod listview;
 pub mod log_viewer;
 pub mod popup;
@@ -11,6 +20,8 @@ mod prelude_internal;
 pub mod prelude;
 pub mod tabcontroller;
 mod mainlooper;
+mod loader;
+mod panic;
 
 use prelude_internal::*;
 
@@ -22,6 +33,14 @@ pub trait Model {
     fn init(&mut self) -> Message {
         Default::default()
     }
+    // Slow work to finish before the model is shown, such as loading its
+    // data. Called once after init; the runner draws a spinner in place of
+    // the view until the task resolves, then handles its message as if
+    // update had returned it, followed by any input that arrived meanwhile.
+    // Headless runs wait for the task before the first frame.
+    fn init_async(&mut self) -> Option<tasks::Task> {
+        None
+    }
     fn update(&mut self, _msg: Message) -> Message {
         Default::default()
     }
@@ -30,12 +49,92 @@ pub trait Model {
     }
 
     fn set_focus(&mut self, _focused: styles::FocusState) { }
+
+    // Called on the root model when the program is asked to quit.
+    // Return false to keep running, e.g. to ask for confirmation first.
+    fn confirm_quit(&mut self) -> bool {
+        true
+    }
+
+    // Called on the root model once it has stopped, before the terminal
+    // is restored, to flush state or save files. An error here is returned
+    // from run unless the program had already failed.
+    fn on_exit(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    // Recurring message sources the model wants running right now.
+    // Called after every update; see subscription::Subscription.
+    fn subscriptions(&self) -> Vec<subscription::Subscription> {
+        Vec::new()
+    }
 }
 
 pub trait Runner : Model + Sized {
     fn run(&mut self) -> impl std::future::Future<Output=Result<()>> {
+        self.run_with(Default::default())
+    }
+
+    fn run_with(&mut self, options: options::RunOptions) -> impl std::future::Future<Output=Result<()>> {
+        self.run_with_handle(options, |_| ())
+    }
+
+    // Like run_with, but hands a ProgramHandle to on_start before the
+    // loop begins, so other tasks and threads can send messages in
+    fn run_with_handle(
+        &mut self,
+        options: options::RunOptions,
+        on_start: impl FnOnce(program::ProgramHandle),
+    ) -> impl std::future::Future<Output=Result<()>> {
         async {
-            mainlooper::MainLooper::run(self).await
+            mainlooper::MainLooper::run(self, options, on_start).await
+        }
+    }
+
+    // Run against a test backend instead of the terminal, feeding in the
+    // scripted messages in order. Returns the model along with the frame
+    // rendered after init and after each message. Commands are waited for
+    // before each frame is rendered, so what they report back shows up in
+    // it; anything they start in turn shows up in the next frame.
+    fn run_headless(
+        mut self,
+        backend: ratatui::backend::TestBackend,
+        script: impl IntoIterator<Item=Message>,
+    ) -> impl std::future::Future<Output=Result<(Self, Vec<ratatui::buffer::Buffer>)>> {
+        async move {
+            let frames = mainlooper::MainLooper::run_headless(&mut self, backend, script).await?;
+            Ok((self, frames))
+        }
+    }
+
+    // Replay a recording made with RunOptions::with_recording against a
+    // test backend of the recorded size
+    fn replay(
+        self,
+        path: impl AsRef<std::path::Path>,
+    ) -> impl std::future::Future<Output=Result<(Self, Vec<ratatui::buffer::Buffer>)>> {
+        let recording = recording::load(path);
+        async move {
+            let (backend, script) = recording::to_script(&recording?);
+            self.run_headless(backend, script).await
+        }
+    }
+}
+
+// A runner with something to hand back once it has quit, such as what the
+// user chose, so callers needn't reach back into the model for it
+pub trait RunnerWithOutput : Runner {
+    type Output;
+    fn output(&mut self) -> Self::Output;
+
+    fn run_for_output(&mut self) -> impl std::future::Future<Output=Result<Self::Output>> {
+        self.run_for_output_with(Default::default())
+    }
+
+    fn run_for_output_with(&mut self, options: options::RunOptions) -> impl std::future::Future<Output=Result<Self::Output>> {
+        async {
+            self.run_with(options).await?;
+            Ok(self.output())
         }
     }
 }
@@ -60,6 +159,12 @@ impl<M: Model> Model for Option<M> {
             None => Message::Noop,
         }
     }
+    fn init_async(&mut self) -> Option<tasks::Task> {
+        match self {
+            Some(inner) => inner.init_async(),
+            None => None,
+        }
+    }
     fn update(&mut self, msg: Message) -> Message {
         match self {
             Some(inner) => inner.update(msg),
@@ -74,9 +179,29 @@ impl<M: Model> Model for Option<M> {
     }
 
     fn set_focus(&mut self, focused: styles::FocusState) {
+        if let Some(inner) = self {
+            inner.set_focus(focused);
+        }
+    }
+
+    fn confirm_quit(&mut self) -> bool {
+        match self {
+            Some(inner) => inner.confirm_quit(),
+            None => true,
+        }
+    }
+
+    fn on_exit(&mut self) -> Result<()> {
+        match self {
+            Some(inner) => inner.on_exit(),
+            None => Ok(()),
+        }
+    }
+
+    fn subscriptions(&self) -> Vec<subscription::Subscription> {
         match self {
-            Some(inner) => inner.set_focus(focused),
-            None => (),
+            Some(inner) => inner.subscriptions(),
+            None => Vec::new(),
         }
     }
 }
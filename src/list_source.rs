@@ -0,0 +1,219 @@
+// Where a ListView gets its items from
+use crate::prelude_internal::*;
+use crate::shell::{OutputStream, ShellCommand};
+use crate::tasks::Task;
+use std::fmt::Debug;
+use std::future::Future;
+use std::pin::Pin;
+use uuid::Uuid;
+
+// Items for a ListView to show. A source may grow while the list is
+// showing, but items already in it shouldn't change; a source that
+// shrinks is treated as a whole new set of items.
+pub trait ListSource<Item> {
+    fn len(&self) -> usize;
+    fn get(&self, index: usize) -> Option<&Item>;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Run when the list is initialized, e.g. to start loading
+    fn init(&mut self) -> Message {
+        Message::Noop
+    }
+    // Every message the list gets is offered here first. Return Some with
+    // the message to send on if it was meant for the source, after which
+    // the list picks up any new items.
+    fn update(&mut self, _msg: &Message) -> Option<Message> {
+        None
+    }
+
+    // Whether items are on their way, to show a "loading more…" row
+    fn is_loading(&self) -> bool {
+        false
+    }
+    // Whether load_more could find more items
+    fn has_more(&self) -> bool {
+        false
+    }
+    // Called when the selection nears the end of the items while has_more
+    // is true and nothing is loading; returns the command that loads them
+    fn load_more(&mut self) -> Message {
+        Message::Noop
+    }
+}
+
+impl<Item> ListSource<Item> for Vec<Item> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+    fn get(&self, index: usize) -> Option<&Item> {
+        self.as_slice().get(index)
+    }
+}
+
+type PageFuture<Item> = Pin<Box<dyn Future<Output=Result<Vec<Item>>> + Send>>;
+type FetchFn<Item> = Box<dyn Fn(usize, usize) -> PageFuture<Item> + Send>;
+type ParseFn<Item> = Box<dyn Fn(&str) -> Option<Item> + Send>;
+
+// A page of items for the PagedSource with this id
+#[derive(Debug, Clone)]
+struct Page<Item> {
+    id: Uuid,
+    items: Result<Vec<Item>>,
+}
+
+// Loads items a page at a time as the user scrolls down. The fetch
+// function is given the offset and size of the page to load; a page with
+// fewer items than that, or with none at all, is taken to be the last one.
+pub struct PagedSource<Item> {
+    id: Uuid,
+    items: Vec<Item>,
+    page_size: usize,
+    fetch: FetchFn<Item>,
+    loading: bool,
+    exhausted: bool,
+}
+
+impl<Item: Clone + Debug + Send + 'static> PagedSource<Item> {
+    pub fn new<F, Fut>(page_size: usize, fetch: F) -> Self
+    where
+        F: Fn(usize, usize) -> Fut + Send + 'static,
+        Fut: Future<Output=Result<Vec<Item>>> + Send + 'static,
+    {
+        Self {
+            id: Uuid::new_v4(),
+            items: Vec::new(),
+            page_size,
+            fetch: Box::new(move |offset, limit| Box::pin(fetch(offset, limit))),
+            loading: false,
+            exhausted: false,
+        }
+    }
+}
+
+impl<Item> Debug for PagedSource<Item> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PagedSource({}, {} items)", self.id, self.items.len())
+    }
+}
+
+impl<Item: Clone + Debug + Send + 'static> ListSource<Item> for PagedSource<Item> {
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+    fn get(&self, index: usize) -> Option<&Item> {
+        self.items.get(index)
+    }
+    fn init(&mut self) -> Message {
+        self.load_more()
+    }
+    fn update(&mut self, msg: &Message) -> Option<Message> {
+        let page = msg.downcast_ref::<Page<Item>>().filter(|page| page.id == self.id)?;
+        self.loading = false;
+        match &page.items {
+            Ok(items) => {
+                self.exhausted = items.is_empty() || items.len() < self.page_size;
+                self.items.extend(items.iter().cloned());
+                Some(Message::Noop)
+            }
+            Err(err) => {
+                // Don't keep asking for a page that won't load
+                self.exhausted = true;
+                Some(Message::Error(err.clone()))
+            }
+        }
+    }
+    fn is_loading(&self) -> bool {
+        self.loading
+    }
+    fn has_more(&self) -> bool {
+        !self.exhausted
+    }
+    fn load_more(&mut self) -> Message {
+        if self.loading || self.exhausted {
+            return Message::Noop;
+        }
+        self.loading = true;
+        let id = self.id;
+        let page = (self.fetch)(self.items.len(), self.page_size);
+        Message::Task(Task::new(async move {
+            Message::custom(Page { id, items: page.await })
+        }))
+    }
+}
+
+// Fills the list with the output of a shell command as it runs, one item
+// per line of standard output. Lines that parse to None are skipped.
+pub struct StreamedSource<Item> {
+    command: Option<ShellCommand>,
+    id: Uuid,
+    items: Vec<Item>,
+    parse: ParseFn<Item>,
+    running: bool,
+}
+
+impl<Item> StreamedSource<Item> {
+    pub fn new(command: ShellCommand, parse: impl Fn(&str) -> Option<Item> + Send + 'static) -> Self {
+        Self {
+            id: command.id(),
+            command: Some(command),
+            items: Vec::new(),
+            parse: Box::new(parse),
+            running: false,
+        }
+    }
+}
+
+impl StreamedSource<String> {
+    // One item for every line of output
+    pub fn lines(command: ShellCommand) -> Self {
+        Self::new(command, |line| Some(line.to_string()))
+    }
+}
+
+impl<Item> Debug for StreamedSource<Item> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "StreamedSource({}, {} items)", self.id, self.items.len())
+    }
+}
+
+impl<Item> ListSource<Item> for StreamedSource<Item> {
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+    fn get(&self, index: usize) -> Option<&Item> {
+        self.items.get(index)
+    }
+    fn init(&mut self) -> Message {
+        match self.command.take() {
+            Some(command) => {
+                self.running = true;
+                Message::shell(command).1
+            }
+            None => Message::Noop,
+        }
+    }
+    fn update(&mut self, msg: &Message) -> Option<Message> {
+        match msg {
+            Message::ShellCommandLine { id, stream: OutputStream::Stdout, line } if *id == self.id => {
+                if let Some(item) = (self.parse)(line) {
+                    self.items.push(item);
+                }
+                Some(Message::Noop)
+            }
+            Message::ShellCommandExited { id, status } if *id == self.id => {
+                self.running = false;
+                match status {
+                    Some(0) => Some(Message::Noop),
+                    Some(code) => Some(Message::Error(Error::OwnedError(format!("command exited with status {code}")))),
+                    None => Some(Message::errorf("command did not finish")),
+                }
+            }
+            _ => None,
+        }
+    }
+    fn is_loading(&self) -> bool {
+        self.running
+    }
+}
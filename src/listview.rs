@@ -1,9 +1,16 @@
 use crate::prelude_internal::*;
-use ratatui::widgets::ListState;
-use ratatui::crossterm::event::KeyCode;
+use ratatui::widgets::{HighlightSpacing, ListItem, ListState};
+use ratatui::crossterm::event::{KeyCode, MouseEvent, MouseEventKind};
+use ratatui::layout::{Margin, Position};
 use crate::input_control::InputControl;
+use crate::list_source::ListSource;
+use crate::matcher::{Match, MatchMode, Matcher, SearchKey};
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use std::collections::BTreeSet;
 use std::fmt::Display;
 use std::iter::IntoIterator;
+use std::sync::Arc;
 
 #[derive(Debug, PartialEq, Eq, Default)]
 enum ListViewMode {
@@ -13,76 +20,432 @@ enum ListViewMode {
     Filtered,
 }
 
+// The items matching the current search
 #[derive(Debug, Default)]
-pub struct ListView<Item: Display + Clone> {
+struct Filter {
+    query: String,
+    matcher: Matcher,
+    // Indexes into items, in the order shown, with what matched
+    matches: Vec<(usize, Match)>,
+}
+
+// Start loading more items when the selection is this close to the end
+const LOAD_AHEAD: usize = 5;
+
+// What a renderer is told about the item it is drawing
+#[derive(Debug, Clone, Copy)]
+pub struct ItemState<'a> {
+    // The highlighted item, which the list also styles itself
+    pub selected: bool,
+    // Whether the item is marked; None unless in multi-select mode
+    pub marked: Option<bool>,
+    // The item's search key, and which of its chars matched the search
+    pub key: &'a str,
+    pub matched: &'a [usize],
+    // Columns the item has to draw in, after the list's highlight symbol
+    pub width: u16,
+    pub focus: FocusState,
+}
+
+impl ItemState<'_> {
+    // The text, usually the key, with the chars at the matched positions
+    // styled
+    pub fn highlight(&self, text: &str) -> Line<'static> {
+        let style = STYLES.style_for(self.focus).matched;
+        // Group the chars into runs that all matched or all didn't
+        let mut spans = Vec::new();
+        let mut positions = self.matched.iter().peekable();
+        let mut run = String::new();
+        let mut run_matched = false;
+        for (j, c) in text.chars().enumerate() {
+            let is_match = positions.next_if_eq(&&j).is_some();
+            if is_match != run_matched && !run.is_empty() {
+                let text = std::mem::take(&mut run);
+                spans.push(if run_matched { Span::styled(text, style) } else { Span::raw(text) });
+            }
+            run_matched = is_match;
+            run.push(c);
+        }
+        if !run.is_empty() {
+            spans.push(if run_matched { Span::styled(run, style) } else { Span::raw(run) });
+        }
+        Line::from(spans)
+    }
+}
+
+type RenderFn<Item> = dyn Fn(&Item, &ItemState) -> ListItem<'static> + Send + Sync;
+type KeyFn<Item> = dyn Fn(&Item) -> String + Send + Sync;
+
+// A closure customizing the list, kept when with_items makes a new one
+struct Hook<F: ?Sized>(Arc<F>);
+
+impl<F: ?Sized> Clone for Hook<F> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<F: ?Sized> std::fmt::Debug for Hook<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Hook")
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ListView<Item: Display + Clone, Source: ListSource<Item> = Vec<Item>> {
     title: String,
-    items: Vec<Item>,
+    source: Source,
+    // What each item is searched by, made once rather than per keystroke
+    keys: Vec<SearchKey>,
+    filter: Filter,
     state: ListState,
     search: InputControl,
     mode: ListViewMode,
     focus: FocusState,
+    // Where the list was last drawn, for hit-testing the mouse
+    list_area: Rect,
+    // Let several items be marked and chosen together
+    multi_select: bool,
+    // Indexes into items of the marked items
+    marked: BTreeSet<usize>,
+    match_mode: MatchMode,
+    // Draws an item instead of Display
+    renderer: Option<Hook<RenderFn<Item>>>,
+    // Makes an item's search key instead of Display
+    search_key: Option<Hook<KeyFn<Item>>>,
+    // How many rows each item shown took up, for hit-testing the mouse
+    heights: Vec<usize>,
     pub chosen: Option<Item>,
+    // Set on Enter in multi-select mode
+    pub chosen_items: Vec<Item>,
 }
 
 impl<Item: Display + Clone> ListView<Item> {
     pub fn new(title: &str, items: impl IntoIterator<Item=Item>) -> Self {
-        let items = items.into_iter().collect();
-        Self {
+        Self::from_source(title, items.into_iter().collect())
+    }
+    pub fn with_items(&self, items: impl IntoIterator<Item=Item>) -> Self {
+        let mut list = Self::new(&self.title, items)
+            .with_multi_select(self.multi_select)
+            .with_match_mode(self.match_mode);
+        list.renderer = self.renderer.clone();
+        if let Some(search_key) = &self.search_key {
+            list.search_key = Some(search_key.clone());
+            list.rekey();
+        }
+        list
+    }
+}
+
+impl<Item: Display + Clone, Source: ListSource<Item>> ListView<Item, Source> {
+    pub fn from_source(title: &str, source: Source) -> Self {
+        let mut list = Self {
             title: title.to_string(),
-            items,
+            source,
+            keys: Vec::new(),
+            filter: Filter::default(),
             state: ListState::default(),
             search: InputControl::from_value(""),
             mode: Default::default(),
             focus: FocusState::Blur,
+            list_area: Rect::default(),
+            multi_select: false,
+            marked: BTreeSet::new(),
+            match_mode: MatchMode::default(),
+            renderer: None,
+            search_key: None,
+            heights: Vec::new(),
             chosen: None,
+            chosen_items: Vec::new(),
+        };
+        list.sync_source();
+        list
+    }
+    pub fn source(&self) -> &Source {
+        &self.source
+    }
+    // Draw items with the closure rather than with Display. It can use
+    // ItemState::highlight on the key to show what matched the search, and
+    // draws its own mark for multi-select mode.
+    pub fn with_renderer(
+        mut self,
+        renderer: impl Fn(&Item, &ItemState) -> ListItem<'static> + Send + Sync + 'static,
+    ) -> Self {
+        self.renderer = Some(Hook(Arc::new(renderer)));
+        self
+    }
+    // Search items by the text the closure makes rather than by Display.
+    // Items are still shown with Display unless there is a renderer.
+    pub fn with_search_key(mut self, search_key: impl Fn(&Item) -> String + Send + Sync + 'static) -> Self {
+        self.search_key = Some(Hook(Arc::new(search_key)));
+        self.rekey();
+        self
+    }
+    fn key_for(&self, item: &Item) -> String {
+        match &self.search_key {
+            Some(Hook(search_key)) => search_key(item),
+            None => item.to_string(),
         }
     }
-    pub fn with_items(&self, items: impl IntoIterator<Item=Item>) -> Self {
-        Self::new(&self.title, items)
+    // Make every search key again
+    fn rekey(&mut self) {
+        self.keys.clear();
+        self.sync_source();
+    }
+    // How the search query is matched against items. Fuzzy matching
+    // lists the best matches first; the others keep the list order.
+    pub fn with_match_mode(mut self, mode: MatchMode) -> Self {
+        self.match_mode = mode;
+        self.refilter(true);
+        self
+    }
+    // Space marks and unmarks the selected item, 'a' and 'A' mark and
+    // unmark everything matching the search, and Enter chooses all the
+    // marked items, or the selected one if none are marked, sending
+    // Message::Choices with their indexes in the full list
+    pub fn with_multi_select(mut self, enabled: bool) -> Self {
+        self.multi_select = enabled;
+        self
+    }
+    // The marked items, in list order
+    pub fn marked(&self) -> impl Iterator<Item=&Item> {
+        self.marked.iter().filter_map(|&i| self.source.get(i))
+    }
+    // Pick up items added to the source since last time, matching just
+    // the new ones against the search
+    fn sync_source(&mut self) {
+        let len = self.source.len();
+        if len < self.keys.len() {
+            self.keys.clear();
+            self.marked.clear();
+            self.state.select(None);
+        }
+        let start = self.keys.len();
+        for i in start..len {
+            let text = self.source.get(i).map(|item| self.key_for(item)).unwrap_or_default();
+            self.keys.push(SearchKey::new(text));
+        }
+        if start == 0 {
+            self.refilter(true);
+        } else {
+            let fuzzy = self.match_mode == MatchMode::Fuzzy;
+            let mut row = self.state.selected();
+            for i in start..len {
+                let Some(m) = self.filter.matcher.matches_key(&self.keys[i]) else {
+                    continue;
+                };
+                if !fuzzy {
+                    self.filter.matches.push((i, m));
+                    continue;
+                }
+                // Insert in rank order. New items may rank above the
+                // selected one, which should stay selected wherever it
+                // ends up.
+                let rank = (std::cmp::Reverse(m.score), i);
+                let pos = self.filter.matches.partition_point(|(j, n)| (std::cmp::Reverse(n.score), *j) < rank);
+                self.filter.matches.insert(pos, (i, m));
+                if let Some(r) = row.filter(|r| pos <= *r) {
+                    row = Some(r + 1);
+                }
+            }
+            if row != self.state.selected() {
+                self.state.select(row);
+            }
+        }
+        self.clamp_selection();
+    }
+    // Keep the selection on an item that is showing, selecting the first
+    // if nothing is selected
+    fn clamp_selection(&mut self) {
+        let len = self.filtered_len();
+        let row = match self.state.selected() {
+            _ if len == 0 => None,
+            Some(row) => Some(row.min(len - 1)),
+            None => Some(0),
+        };
+        if row != self.state.selected() {
+            self.state.select(row);
+        }
+    }
+    // Ask the source for more once the selection nears the end
+    fn load_ahead(&mut self) -> Message {
+        let near_end = self.state.selected().unwrap_or(0) + LOAD_AHEAD >= self.filtered_len();
+        if near_end && self.source.has_more() && !self.source.is_loading() {
+            self.source.load_more()
+        } else {
+            Message::Noop
+        }
+    }
+    // Match the items against the search query if it has changed. When
+    // the query has only been typed onto, just the items that already
+    // matched are checked again.
+    fn refilter(&mut self, force: bool) {
+        let query = self.search.value();
+        if !force && query == self.filter.query && self.match_mode == self.filter.matcher.mode() {
+            return;
+        }
+        let matcher = Matcher::new(self.match_mode, &query);
+        let candidates: Vec<usize> = if !force && matcher.narrows(&self.filter.matcher) {
+            self.filter.matches.iter().map(|(i, _)| *i).collect()
+        } else {
+            (0..self.keys.len()).collect()
+        };
+        let mut matches: Vec<(usize, Match)> = candidates.into_iter()
+            .filter_map(|i| Some((i, matcher.matches_key(&self.keys[i])?)))
+            .collect();
+        if self.match_mode == MatchMode::Fuzzy {
+            // Equally good matches keep their list order
+            matches.sort_by_key(|(i, m)| (std::cmp::Reverse(m.score), *i));
+        }
+        self.filter = Filter { query, matcher, matches };
+        self.clamp_selection();
+    }
+    // Items matching the search, with their indexes in the full list
+    fn filtered(&self) -> impl Iterator<Item=(usize, &Item)> {
+        self.filter.matches.iter().filter_map(|(i, _)| Some((*i, self.source.get(*i)?)))
     }
-    fn filtered_items(&self) -> impl Iterator<Item=&Item> {
-        let query = self.search.value().to_lowercase();
-        self.items.iter().filter(move |item| {
-            item.to_string().to_lowercase().contains(&query)
-        })
+    fn selected_index(&self) -> Option<usize> {
+        let idx = self.state.selected()?;
+        self.filter.matches.get(idx).map(|(i, _)| *i)
     }
+    // The item at this index in the full list, as sent by Choice and Choices
+    pub fn get(&self, index: usize) -> Option<&Item> {
+        self.source.get(index)
+    }
+    // The item in this row of the list as the search shows it
     pub fn filtered_get(&self, index: usize) -> Option<&Item> {
-        self.filtered_items().nth(index)
+        let (i, _) = self.filter.matches.get(index)?;
+        self.source.get(*i)
     }
     pub fn selected(&self) -> Option<&Item> {
-        let idx = match self.state.selected() {
-            None => return None,
-            Some(idx) => idx,
-        };
+        let idx = self.state.selected()?;
         self.filtered_get(idx)
     }
     fn filtered_len(&self) -> usize {
-        self.filtered_items().count()
+        self.filter.matches.len()
     }
     fn filtered_is_empty(&self) -> bool {
         self.filtered_len() == 0
     }
+    fn end_search(&mut self) {
+        if self.mode == ListViewMode::Searching {
+            self.mode = if self.search.value().is_empty() {
+                ListViewMode::Normal
+            } else {
+                ListViewMode::Filtered
+            };
+        }
+    }
+    fn toggle_marked(&mut self) -> Message {
+        let Some(i) = self.selected_index() else {
+            return Message::Noop;
+        };
+        if !self.marked.remove(&i) {
+            self.marked.insert(i);
+        }
+        Message::Redraw
+    }
+    fn mark_filtered(&mut self, mark: bool) -> Message {
+        let indexes: Vec<usize> = self.filtered().map(|(i, _)| i).collect();
+        for i in indexes {
+            if mark {
+                self.marked.insert(i);
+            } else {
+                self.marked.remove(&i);
+            }
+        }
+        Message::Redraw
+    }
+    fn choose_marked(&mut self) -> Message {
+        let indexes: Vec<usize> = if self.marked.is_empty() {
+            self.selected_index().into_iter().collect()
+        } else {
+            self.marked.iter().copied().collect()
+        };
+        if indexes.is_empty() {
+            return Message::Noop;
+        }
+        self.chosen_items = indexes.iter().filter_map(|&i| self.source.get(i).cloned()).collect();
+        Message::choose_many(indexes)
+    }
+    fn update_search(&mut self, msg: Message) -> Message {
+        let next = self.search.update(msg);
+        self.refilter(false);
+        next
+    }
+    fn handle_mouse(&mut self, mouse: MouseEvent) -> Message {
+        let position = Position::new(mouse.column, mouse.row);
+        if !self.list_area.contains(position) {
+            return Message::Noop;
+        }
+        let fl = self.filtered_len();
+        if fl == 0 {
+            return Message::Noop;
+        }
+        match mouse.kind {
+            MouseEventKind::Down(_) => {
+                // Items may take up more than one line each
+                let mut y = (mouse.row - self.list_area.y) as usize;
+                let mut row = self.state.offset();
+                while row < fl && y >= self.heights.get(row).copied().unwrap_or(1) {
+                    y -= self.heights.get(row).copied().unwrap_or(1);
+                    row += 1;
+                }
+                if row >= fl {
+                    return Message::Noop;
+                }
+                self.end_search();
+                self.state.select(Some(row));
+                Message::Redraw
+            }
+            MouseEventKind::ScrollDown => {
+                self.state.select(Some(self.state.selected().map_or(0, |i| (i + 1).min(fl - 1))));
+                Message::Redraw.and(self.load_ahead())
+            }
+            MouseEventKind::ScrollUp => {
+                self.state.select(Some(self.state.selected().map_or(0, |i| i.saturating_sub(1))));
+                Message::Redraw
+            }
+            _ => Message::Noop,
+        }
+    }
 }
 
-impl<Item: Display + Clone> Model for ListView<Item> {
+impl<Item: Display + Clone, Source: ListSource<Item>> Model for ListView<Item, Source> {
     fn init(&mut self) -> Message {
-        if self.items.is_empty() {
-            return Message::Noop
+        let init = self.source.init();
+        self.sync_source();
+        if self.filtered_is_empty() {
+            return init;
         }
         self.state.select(Some(0));
-        Message::Redraw
+        Message::Redraw.and(init)
     }
     fn set_focus(&mut self, focus: FocusState) {
         self.focus = focus;
     }
 
     fn update(&mut self, msg: Message) -> Message {
+        if let Some(next) = self.source.update(&msg) {
+            self.sync_source();
+            return Message::Redraw.and(next).and(self.load_ahead());
+        }
+        if let Message::Mouse(mouse) = msg {
+            return self.handle_mouse(mouse);
+        }
+        if let Message::Paste(_) = msg {
+            return if self.mode == ListViewMode::Searching {
+                self.update_search(msg)
+            } else {
+                Message::Noop
+            };
+        }
         if let Message::KeyPress(key) = msg {
             match key.code {
                 KeyCode::Up | KeyCode::Char('k') | KeyCode::Char('w') => {
                     match self.mode {
                         ListViewMode::Searching => {
-                            self.search.update(msg)
+                            self.update_search(msg)
                         }
                         _ => {
                             let fl = self.filtered_len();
@@ -115,7 +478,7 @@ impl<Item: Display + Clone> Model for ListView<Item> {
                                 self.state.select(if self.filtered_len() == 0 { None } else { Some(0) });
                                 Message::Redraw
                             } else {
-                                self.search.update(msg)
+                                self.update_search(msg)
                             }
                         _ => {
                             let fl = self.filtered_len();
@@ -132,7 +495,7 @@ impl<Item: Display + Clone> Model for ListView<Item> {
                                 }
                             };
                             self.state.select(i);
-                            Message::Redraw
+                            Message::Redraw.and(self.load_ahead())
                         }
                     }
                 }
@@ -149,8 +512,10 @@ impl<Item: Display + Clone> Model for ListView<Item> {
                         }
                         _ => if self.filtered_is_empty() {
                                 Message::errorf("No items match the search query.")
-                            } else if let Some(i) = self.state.selected() {
-                                self.chosen = self.filtered_get(i).cloned();
+                            } else if self.multi_select {
+                                self.choose_marked()
+                            } else if let Some(i) = self.selected_index() {
+                                self.chosen = self.source.get(i).cloned();
                                 Message::choose(i)
                             } else {
                                 Message::Noop
@@ -160,11 +525,18 @@ impl<Item: Display + Clone> Model for ListView<Item> {
                 KeyCode::Char('/') => {
                     self.mode = ListViewMode::Searching;
                     self.search.set_value("");
+                    self.refilter(false);
                     Message::Redraw
                 }
+                KeyCode::Char(' ') if self.multi_select && self.mode != ListViewMode::Searching => {
+                    self.toggle_marked()
+                }
+                KeyCode::Char(c @ ('a' | 'A')) if self.multi_select && self.mode != ListViewMode::Searching => {
+                    self.mark_filtered(c == 'a')
+                }
                 _ => {
                     if self.mode == ListViewMode::Searching {
-                        self.search.update(msg)
+                        self.update_search(msg)
                     } else {
                         Message::Noop
                     }
@@ -200,7 +572,47 @@ impl<Item: Display + Clone> Model for ListView<Item> {
                 }
             }
         };
-        let items = self.filtered_items().map(|item| ratatui::widgets::ListItem::new(item.to_string())).collect::<Vec<_>>();
+        self.list_area = list_area.inner(Margin::new(1, 1));
+        let style = STYLES.style_for(self.focus);
+        let symbol = match style.highlight_spacing {
+            HighlightSpacing::Never => 0,
+            HighlightSpacing::WhenSelected if self.state.selected().is_none() => 0,
+            _ => Line::from(style.highlight_symbol).width() as u16,
+        };
+        let width = self.list_area.width.saturating_sub(symbol);
+        let items: Vec<ListItem> = self.filter.matches.iter().enumerate().filter_map(|(row, (i, m))| {
+            let item = self.source.get(*i)?;
+            let state = ItemState {
+                selected: self.state.selected() == Some(row),
+                marked: self.multi_select.then(|| self.marked.contains(i)),
+                key: self.keys[*i].text(),
+                matched: &m.positions,
+                width,
+                focus: self.focus,
+            };
+            Some(match &self.renderer {
+                Some(Hook(renderer)) => renderer(item, &state),
+                None => {
+                    // Only show what matched if the key is what's shown
+                    let mut line = match self.search_key {
+                        None => state.highlight(state.key),
+                        Some(_) => match item.to_string() {
+                            text if text == state.key => state.highlight(&text),
+                            text => Line::raw(text),
+                        },
+                    };
+                    if let Some(marked) = state.marked {
+                        line.spans.insert(0, Span::raw(if marked { "[x] " } else { "[ ] " }));
+                    }
+                    ListItem::new(line)
+                }
+            })
+        }).collect();
+        self.heights = items.iter().map(ListItem::height).collect();
+        let loading = self.source.is_loading().then(|| {
+            ListItem::new(Span::styled("loading more…", Modifier::ITALIC))
+        });
+        let items = items.into_iter().chain(loading).collect::<Vec<_>>();
         STYLES.render_list(
             &self.title,
             self.focus,
@@ -214,6 +626,9 @@ impl<Item: Display + Clone> Model for ListView<Item> {
     }
 
     fn help(&self) -> Option<String> {
+        if self.multi_select {
+            return Some("Use Up/Down arrows or 'j'/'k' to navigate, Space to mark, 'a'/'A' to mark/unmark all, Enter to choose marked, / to toggle filtering".to_string());
+        }
         Some("Use Up/Down arrows or 'j'/'k' to navigate, Enter to select, / to toggle filtering".to_string())
     }
 }
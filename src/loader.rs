@@ -0,0 +1,108 @@
+use crate::prelude_internal::*;
+use crate::spinner::Spinner;
+use crate::tasks::Task;
+use ratatui::widgets::Borders;
+
+// What the model's init_async task resolved to, tagged so the loader can
+// tell it apart from everything else headed for the model
+#[derive(Debug, Clone)]
+struct Loaded(Box<Message>);
+
+// Wraps the root model while the runner drives it, drawing a spinner in
+// place of the view until the model's init_async task has resolved
+pub(crate) struct Loader<'m, M: Model> {
+    model: &'m mut M,
+    spinner: Spinner,
+    loading: bool,
+    // Input that arrived while loading, for the model once it has loaded
+    held: Vec<Message>,
+}
+
+impl<'m, M: Model> Loader<'m, M> {
+    pub fn new(model: &'m mut M) -> Self {
+        Self {
+            model,
+            spinner: Spinner::dots(),
+            loading: false,
+            held: Vec::new(),
+        }
+    }
+}
+
+impl<M: Model> Model for Loader<'_, M> {
+    fn view(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        if !self.loading {
+            return self.model.view(frame, area);
+        }
+        frame.render_widget(Paragraph::new("").block(STYLES.blur.block.clone().borders(Borders::NONE)), area);
+        let [_, row, _] = Layout::vertical([
+            Constraint::Min(0),
+            Constraint::Length(1),
+            Constraint::Min(0),
+        ]).areas(area);
+        frame.render_widget(
+            Paragraph::new(format!("{} Loading…", self.spinner)).centered(),
+            row,
+        );
+        Ok(())
+    }
+
+    fn init(&mut self) -> Message {
+        let init = self.model.init();
+        let Some(task) = self.model.init_async() else {
+            return init;
+        };
+        self.loading = true;
+        let load = Task::new(async move {
+            match task.execute().await {
+                Some(msg) => Message::custom(Loaded(Box::new(msg))),
+                None => Message::Noop,
+            }
+        });
+        init.and(self.spinner.init()).and(Message::Task(load))
+    }
+
+    fn update(&mut self, msg: Message) -> Message {
+        if !self.loading {
+            return self.model.update(msg);
+        }
+        match msg {
+            msg if msg.is::<Loaded>() => {
+                self.loading = false;
+                let held = Message::Batch(std::mem::take(&mut self.held));
+                // Hand the loaded message back to the runner, so that one
+                // it handles itself, such as Quit, isn't given to the model
+                match msg.downcast::<Loaded>() {
+                    Ok(Loaded(msg)) => Message::Sequence(vec![*msg, Message::Redraw, held]),
+                    Err(_) => Message::Redraw.and(held),
+                }
+            }
+            // Nothing to act on yet
+            Message::KeyPress(_) | Message::Mouse(_) | Message::Paste(_) => {
+                self.held.push(msg);
+                Message::Noop
+            }
+            msg => self.spinner.update(msg.clone()).and(self.model.update(msg)),
+        }
+    }
+
+    fn help(&self) -> Option<String> {
+        self.model.help()
+    }
+
+    fn set_focus(&mut self, focused: FocusState) {
+        self.model.set_focus(focused);
+    }
+
+    fn confirm_quit(&mut self) -> bool {
+        self.model.confirm_quit()
+    }
+
+    fn on_exit(&mut self) -> Result<()> {
+        self.model.on_exit()
+    }
+
+    fn subscriptions(&self) -> Vec<crate::subscription::Subscription> {
+        self.model.subscriptions()
+    }
+}
@@ -154,6 +154,34 @@ impl<M: Model> Model for LogViewer<M> {
     fn init(&mut self) -> Message {
         self.model.init()
     }
+    fn init_async(&mut self) -> Option<crate::tasks::Task> {
+        self.model.init_async()
+    }
+    fn confirm_quit(&mut self) -> bool {
+        self.model.confirm_quit()
+    }
+    fn on_exit(&mut self) -> Result<()> {
+        self.model.on_exit()
+    }
+    fn subscriptions(&self) -> Vec<crate::subscription::Subscription> {
+        let mut subscriptions = self.model.subscriptions();
+        // Keep the log up to date while it is showing
+        if self.view_state != LogViewState::Hidden {
+            subscriptions.push(crate::subscription::Subscription::interval(
+                "raccacoonie::log_viewer",
+                tokio::time::Duration::from_millis(250),
+                Message::Redraw,
+            ));
+        }
+        subscriptions
+    }
 }
 
 impl<M: Model> Runner for LogViewer<M> {}
+
+impl<M: RunnerWithOutput> RunnerWithOutput for LogViewer<M> {
+    type Output = M::Output;
+    fn output(&mut self) -> M::Output {
+        self.model.output()
+    }
+}
@@ -1,17 +1,40 @@
 use crate::prelude_internal::*;
-use ratatui::crossterm::event::{
-    read,
-    poll,
-    Event,
-    KeyCode,
-    KeyModifiers,
+use ratatui::{
+    Terminal,
+    TerminalOptions,
+    Viewport,
+    backend::{Backend, CrosstermBackend, TestBackend},
+    buffer::Buffer,
+    crossterm::{
+        execute,
+        event::{
+            read,
+            poll,
+            Event,
+            EnableMouseCapture,
+            DisableMouseCapture,
+            EnableBracketedPaste,
+            DisableBracketedPaste,
+            EnableFocusChange,
+            DisableFocusChange,
+        },
+        terminal::{enable_raw_mode, disable_raw_mode, EnterAlternateScreen},
+    },
 };
+use crate::subscription::Subscriptions;
+use crate::program::ProgramHandle;
+use crate::panic::catch;
+use crate::loader::Loader;
+use crate::options::GlobalKeys;
+use crate::recording::{Recorder, RecordedEvent};
+use std::sync::Arc;
+use std::collections::VecDeque;
 use tokio::{
-    time::Duration,
+    time::{Duration, Instant, timeout_at},
     sync::mpsc
 };
-// Force a redraw after processing this many events
-const EVENT_BATCH_SIZE: usize = 20;
+// How many messages can be waiting in the event channel
+const EVENT_CHANNEL_SIZE: usize = 20;
 
 type QuitSignal = std::sync::Arc<tokio::sync::SetOnce<()>>;
 fn new_quit_signal() -> QuitSignal {
@@ -19,7 +42,9 @@ fn new_quit_signal() -> QuitSignal {
 }
 
 
-async fn event_loop(tx: mpsc::Sender<Message>, quit: QuitSignal) {
+// Runs on a blocking thread, since reading events would otherwise hold up
+// a runtime worker that commands need
+fn event_loop(tx: mpsc::Sender<Message>, quit: QuitSignal, keys: GlobalKeys, recorder: Option<Arc<Recorder>>) {
     // Listen for key events until told to quit
     loop {
         // If we don't get a key event, check every 100ms if we should quit.
@@ -32,35 +57,33 @@ async fn event_loop(tx: mpsc::Sender<Message>, quit: QuitSignal) {
                 Ok(true) => break,
                 Err(e) => {
                     log::error!("Error polling for key event: {}", e);
-                    let _ = tx.send(Message::Error(e.into())).await;
+                    let _ = tx.blocking_send(Message::Error(e.into()));
                     return;
                 }
             }
         }
-        match read() {
-            Ok(Event::Key(key_event)) => {
-                // Ctrl-C to quit
-                if key_event.modifiers == KeyModifiers::CONTROL && key_event.code == KeyCode::Char('c') {
-                    let _ = tx.send(Message::Quit).await;
-                    return
-                } else {
-                    // Send other key events to the main loop
-                    let _ = tx.send(Message::KeyPress(key_event)).await;
-                };
-            }
+        let msg = match read() {
+            // Global keys like Ctrl-C to quit become their messages,
+            // and other key events go to the main loop
+            Ok(Event::Key(key_event)) => keys.message_for(&key_event)
+                .unwrap_or(Message::KeyPress(key_event)),
+            Ok(Event::Mouse(mouse_event)) => Message::Mouse(mouse_event),
+            Ok(Event::Paste(text)) => Message::Paste(text),
+            Ok(Event::FocusGained) => Message::TerminalFocus(true),
+            Ok(Event::FocusLost) => Message::TerminalFocus(false),
             // Handle terminal resize events
-            Ok(Event::Resize(width, height)) => {
-                let _ = tx.send(Message::Resize(width as usize, height as usize)).await;
-            }
+            Ok(Event::Resize(width, height)) => Message::Resize(width as usize, height as usize),
 
             // Wrap up error events
             Err(e) => {
-                let _ = tx.send(Message::ErrorFatal(e.into())).await;
+                let _ = tx.blocking_send(Message::ErrorFatal(e.into()));
                 return;
             }
-            // ignore other events
-            _ => {}
+        };
+        if let Some(recorder) = &recorder {
+            recorder.record(&msg);
         }
+        let _ = tx.blocking_send(msg);
     }
 }
 
@@ -69,110 +92,513 @@ enum BreakDepth {
     ReadMoreEvents,
     DrawModel,
     EndProgram,
+    // Give up the terminal for a while
+    Suspend,
+    Exec(Vec<String>),
 }
 
 
-pub struct MainLooper<'m, M: Model> {
+// The terminal and the event reader feeding it, which can outlive any one
+// model so several can be run in turn without flicker or lost input
+pub(crate) struct Screen<B: Backend = CrosstermBackend<std::io::Stdout>> {
     quit: QuitSignal,
-    term: ratatui::DefaultTerminal,
+    term: Terminal<B>,
     tx: mpsc::Sender<Message>,
     rx: mpsc::Receiver<Message>,
-    keyloop: tokio::task::JoinHandle<()>,
-    model: &'m mut M,
-
+    // Input that arrived after the last model finished, for the next one
+    leftover: VecDeque<Message>,
+    keyloop: Option<tokio::task::JoinHandle<()>>,
+    recorder: Option<Arc<Recorder>>,
+    options: RunOptions,
+    closed: bool,
 }
-impl<'m, M: Model> MainLooper<'m, M> {
-    fn new(model: &'m mut M) -> Self {
-        let (tx, rx) = mpsc::channel(EVENT_BATCH_SIZE);
-        let quit = new_quit_signal();
+
+impl<B: Backend> Screen<B> {
+    fn with_terminal(term: Terminal<B>, options: RunOptions) -> Self {
+        let (tx, rx) = mpsc::channel(EVENT_CHANNEL_SIZE);
         Self {
-            quit: quit.clone(),
-            term: ratatui::init(),
-            tx: tx.clone(), rx,
-            keyloop: tokio::spawn(event_loop(tx, quit)),
-            model, 
+            quit: new_quit_signal(),
+            term,
+            tx, rx,
+            leftover: VecDeque::new(),
+            keyloop: None,
+            recorder: None,
+            options,
+            closed: false,
         }
     }
 
-    #[inline]
-    async fn inner(&mut self) -> Result<BreakDepth> {
-        match self.rx.try_recv() {
-            // No more events to process right now, proceed to redraw
-            Err(mpsc::error::TryRecvError::Empty) => Ok(BreakDepth::DrawModel),
+    pub fn handle(&self) -> ProgramHandle {
+        ProgramHandle::new(self.tx.clone())
+    }
 
-            // Premature disconnection of the event channel
-            Err(mpsc::error::TryRecvError::Disconnected) => {
+    // Keep whatever the user typed for the next model, but drop anything
+    // still arriving from the last model's commands
+    fn keep_input(&mut self) {
+        while let Ok(msg) = self.rx.try_recv() {
+            if RecordedEvent::from_message(&msg).is_some() {
+                self.leftover.push_back(msg);
+            }
+        }
+    }
+}
+
+impl Screen {
+    pub fn new(options: RunOptions) -> Result<Self> {
+        let recorder = match &options.record {
+            Some(path) => Some(Arc::new(Recorder::create(path)?)),
+            None => None,
+        };
+        let term = match options.inline_height {
+            Some(height) => ratatui::try_init_with_options(TerminalOptions { viewport: Viewport::Inline(height) }),
+            None => ratatui::try_init(),
+        };
+        let term = match term {
+            Ok(term) => term,
+            Err(err) => {
                 ratatui::restore();
-                Err(Error::TerminalError("Premature disconnection of event channel".to_string()))
+                return Err(Error::TerminalError(format!("{err}")));
+            }
+        };
+        let mut screen = Self::with_terminal(term, options);
+        screen.recorder = recorder;
+        if let Err(err) = screen.enable_features() {
+            screen.leave_screen();
+            return Err(err);
+        }
+        screen.start_events();
+        Ok(screen)
+    }
+    fn leave_screen(&mut self) {
+        self.disable_features();
+        let _ = self.term.show_cursor();
+        if self.options.inline_height.is_some() {
+            // Leave the last frame where it is and put the cursor below it
+            let bottom = self.term.get_frame().area().bottom();
+            let _ = self.term.set_cursor_position((0, bottom.saturating_sub(1)));
+            let _ = disable_raw_mode();
+            println!();
+        } else {
+            ratatui::restore();
+        }
+    }
+    fn enter_screen(&mut self) -> Result<()> {
+        enable_raw_mode()?;
+        match self.options.inline_height {
+            // Start a fresh viewport below whatever was printed meanwhile
+            Some(height) => self.term = Terminal::with_options(
+                CrosstermBackend::new(std::io::stdout()),
+                TerminalOptions { viewport: Viewport::Inline(height) },
+            )?,
+            None => execute!(std::io::stdout(), EnterAlternateScreen)?,
+        }
+        self.enable_features()?;
+        self.term.clear()?;
+        Ok(())
+    }
+    fn start_events(&mut self) {
+        self.quit = new_quit_signal();
+        let (tx, quit) = (self.tx.clone(), self.quit.clone());
+        let (keys, recorder) = (self.options.keys.clone(), self.recorder.clone());
+        self.keyloop = Some(tokio::task::spawn_blocking(move || event_loop(tx, quit, keys, recorder)));
+    }
+    // Wait for the event reader to stop so it can't steal input meant for
+    // another program
+    async fn stop_events(&mut self) {
+        let _ = self.quit.set(());
+        if let Some(keyloop) = self.keyloop.take() {
+            let _ = keyloop.await;
+        }
+    }
+    // Put the terminal back the way we found it, run f, then take the
+    // terminal back over
+    async fn release_terminal<T>(&mut self, f: impl AsyncFnOnce() -> T) -> Result<T> {
+        self.stop_events().await;
+        self.leave_screen();
+        let result = f().await;
+        self.enter_screen()?;
+        self.start_events();
+        Ok(result)
+    }
+    // Turn on the optional terminal reporting asked for in the options
+    fn enable_features(&self) -> Result<()> {
+        if self.options.mouse_capture {
+            execute!(std::io::stdout(), EnableMouseCapture)?;
+        }
+        if self.options.bracketed_paste {
+            execute!(std::io::stdout(), EnableBracketedPaste)?;
+        }
+        if self.options.focus_reporting {
+            execute!(std::io::stdout(), EnableFocusChange)?;
+        }
+        Ok(())
+    }
+    fn disable_features(&self) {
+        if self.options.mouse_capture {
+            let _ = execute!(std::io::stdout(), DisableMouseCapture);
+        }
+        if self.options.bracketed_paste {
+            let _ = execute!(std::io::stdout(), DisableBracketedPaste);
+        }
+        if self.options.focus_reporting {
+            let _ = execute!(std::io::stdout(), DisableFocusChange);
+        }
+    }
+    // Restore the terminal and stop reading events; safe to call twice
+    pub fn close(&mut self) {
+        if self.closed {
+            return;
+        }
+        self.closed = true;
+        let _ = self.quit.set(());
+        self.leave_screen();
+        self.rx.close();
+        if let Some(keyloop) = self.keyloop.take() && !keyloop.is_finished() {
+            keyloop.abort();
+        }
+    }
+}
+
+
+pub struct MainLooper<'m, M: Model, B: Backend = CrosstermBackend<std::io::Stdout>> {
+    screen: &'m mut Screen<B>,
+    // Messages returned from update that need no side effects are
+    // delivered from here before anything else in the channel
+    pending: VecDeque<Message>,
+    tasks: Tasks,
+    subscriptions: Subscriptions,
+    model: &'m mut M,
+}
+impl<'m, M: Model, B: Backend> MainLooper<'m, M, B> {
+    fn on_screen(model: &'m mut M, screen: &'m mut Screen<B>) -> Self {
+        let pending = std::mem::take(&mut screen.leftover);
+        Self {
+            screen,
+            pending,
+            tasks: Tasks::new(),
+            subscriptions: Subscriptions::default(),
+            model,
+        }
+    }
+
+    // Queue plain messages locally and hand commands off to be executed
+    fn dispatch(&mut self, msg: Message) {
+        match msg {
+            Message::Noop => (),
+            Message::Batch(msgs) => {
+                for msg in msgs {
+                    self.dispatch(msg);
+                }
+            }
+            Message::Sequence(msgs) if !msgs.iter().any(Message::is_command) => {
+                for msg in msgs {
+                    self.dispatch(msg);
+                }
+            }
+            msg if msg.is_command() => msg.spawn(self.screen.tx.clone(), &self.tasks),
+            msg => self.pending.push_back(msg),
+        }
+    }
+
+    fn next_message(&mut self) -> std::result::Result<Message, mpsc::error::TryRecvError> {
+        match self.pending.pop_front() {
+            Some(msg) => Ok(msg),
+            None => self.screen.rx.try_recv(),
+        }
+    }
+
+    fn process(&mut self, msg: Message) -> Result<BreakDepth> {
+        match msg {
+            // Skip redraw and exit main loop, unless the model objects
+            Message::Quit => if catch(|| self.model.confirm_quit())? {
+                Ok(BreakDepth::EndProgram)
+            } else {
+                Ok(BreakDepth::DrawModel)
             },
-            // Skip redraw and exit main loop
-            Ok(Message::Quit) => Ok(BreakDepth::EndProgram),
 
             // Error occurred, exit with error
-            Ok(Message::ErrorFatal(e)) => {
-                ratatui::restore();
-                return Err(e);
-            },
+            Message::ErrorFatal(e) => Err(e),
 
             // Skip processing and redraw immediately
-            Ok(Message::Redraw) => Ok(BreakDepth::DrawModel),
+            Message::Redraw => Ok(BreakDepth::DrawModel),
+            Message::Repaint => {
+                self.screen.term.clear()?;
+                Ok(BreakDepth::DrawModel)
+            }
+
+            // Leave the terminal to the shell or another program
+            Message::Suspend => Ok(BreakDepth::Suspend),
+            Message::ExecInteractive(cmd) => Ok(BreakDepth::Exec(cmd)),
 
             // Ignore no-op messages
-            Ok(Message::Noop) => Ok(BreakDepth::ReadMoreEvents),
+            Message::Noop => Ok(BreakDepth::ReadMoreEvents),
 
-            // Process other messages
-            Ok(msg) => {
-                self.model.update(msg)
-                    .spawn(self.tx.clone());
+            // Run commands and unpack composites sent in from outside
+            msg @ (Message::Batch(_) | Message::Sequence(_)) => {
+                self.dispatch(msg);
+                Ok(BreakDepth::ReadMoreEvents)
+            }
+            msg if msg.is_command() => {
+                self.dispatch(msg);
                 Ok(BreakDepth::ReadMoreEvents)
             }
+
+            // Process other messages
+            msg => {
+                // The model will need drawing again at the new size
+                let resized = matches!(msg, Message::Resize(..));
+                let next = catch(|| self.model.update(msg))?;
+                self.dispatch(next);
+                self.sync_subscriptions()?;
+                Ok(if resized { BreakDepth::DrawModel } else { BreakDepth::ReadMoreEvents })
+            }
         }
     }
 
+    fn init(&mut self) -> Result<()> {
+        let init = catch(|| self.model.init())?;
+        self.dispatch(init);
+        self.sync_subscriptions()
+    }
+
+    fn sync_subscriptions(&mut self) -> Result<()> {
+        let wanted = catch(|| self.model.subscriptions())?;
+        self.subscriptions.sync(wanted, &self.screen.tx);
+        Ok(())
+    }
+
+    // Stop everything still running on behalf of the model
+    fn stop_background(&mut self) {
+        self.tasks.cancel_all();
+        self.subscriptions.stop_all();
+    }
+
+    // Let the model clean up while the terminal is still ours
+    fn exit(&mut self) -> Result<()> {
+        catch(|| self.model.on_exit())?
+    }
+
+    // Stop the model's commands and hand the screen on to the next model
+    fn finish(mut self) {
+        self.stop_background();
+        self.screen.keep_input();
+    }
+
     #[inline]
-    async fn mainloop(&mut self) -> Result<BreakDepth> {
-        self.term.try_draw(|f: &mut Frame| {
+    async fn inner(&mut self) -> Result<BreakDepth> {
+        match self.next_message() {
+            // No more events to process right now, proceed to redraw
+            Err(mpsc::error::TryRecvError::Empty) => Ok(BreakDepth::DrawModel),
+
+            // Premature disconnection of the event channel
+            Err(mpsc::error::TryRecvError::Disconnected) =>
+                Err(Error::TerminalError("Premature disconnection of event channel".to_string())),
+
+            Ok(msg) => self.process(msg),
+        }
+    }
+
+    fn draw(&mut self) -> Result<()> {
+        // try_draw can only hand back an io::Error, so keep the model's
+        // own error to return instead
+        let mut view_error = None;
+        let model = &mut *self.model;
+        let drawn = self.screen.term.try_draw(|f: &mut Frame| {
             let area = f.area();
-            self.model.view(f, area)
-        })?;
-        // Try to process up to EVENT_BATCH_SIZE events without redrawing
-        // to improve responsiveness.
-        for _ in 0..EVENT_BATCH_SIZE {
-            match self.inner().await {
-                Err(err) => return Err(err),
-                Ok(BreakDepth::ReadMoreEvents) => continue,
-                Ok(bd) => return Ok(bd),
+            catch(|| model.view(f, area))
+                .and_then(|result| result)
+                .inspect_err(|err| view_error = Some(err.clone()))
+        });
+        match view_error {
+            Some(err) => Err(err),
+            None => {
+                drawn?;
+                Ok(())
             }
         }
-        // end 'inner
-        Ok(BreakDepth::DrawModel)
     }
-    fn finalize(mut self) {
-        let _ = self.quit.set(());
-        ratatui::restore();
-        self.rx.close();
-        if !self.keyloop.is_finished() {
-            self.keyloop.abort();
+
+}
+
+impl<'m, M: Model> MainLooper<'m, M> {
+    async fn suspend(&mut self) -> Result<()> {
+        self.screen.release_terminal(async || {
+            #[cfg(unix)]
+            // Stops the process; this returns once it is continued
+            unsafe { libc::raise(libc::SIGTSTP); }
+            #[cfg(not(unix))]
+            log::warn!("Suspending is not supported on this platform");
+        }).await
+    }
+    async fn exec(&mut self, cmd: Vec<String>) -> Result<()> {
+        let status = self.screen.release_terminal(async || {
+            let Some((program, args)) = cmd.split_first() else {
+                return Err(Error::Error("no program to run"));
+            };
+            let status = tokio::process::Command::new(program)
+                .args(args)
+                .status()
+                .await?;
+            Ok(status.code())
+        }).await?;
+        match status {
+            Ok(code) => self.pending.push_back(Message::ExecInteractiveExited(code)),
+            Err(err) => {
+                self.pending.push_back(Message::Error(err));
+                self.pending.push_back(Message::ExecInteractiveExited(None));
+            }
         }
+        Ok(())
     }
-    pub async fn run(model: &'m mut M) -> Result<()> {
-        let mut runner = Self::new(model);
-        runner.model.init().spawn(runner.tx.clone());
+    // Wait for the next message, giving up at the deadline if there is one
+    async fn wait_message(&mut self, deadline: Option<Instant>) -> Result<Option<Message>> {
+        if let Some(msg) = self.pending.pop_front() {
+            return Ok(Some(msg));
+        }
+        let received = match deadline {
+            Some(deadline) => match timeout_at(deadline, self.screen.rx.recv()).await {
+                Ok(received) => received,
+                Err(_) => return Ok(None),
+            },
+            None => self.screen.rx.recv().await,
+        };
+        match received {
+            Some(msg) => Ok(Some(msg)),
+            None => Err(Error::TerminalError("Premature disconnection of event channel".to_string())),
+        }
+    }
+    async fn run_loop(&mut self) -> Result<()> {
+        let frame_time = match self.screen.options.max_fps {
+            0 => Duration::ZERO,
+            fps => Duration::from_secs(1) / fps,
+        };
+        // Only draw when something asked for it, and no more often than
+        // the frame rate allows
+        let mut dirty = true;
+        let mut next_frame = Instant::now();
         loop {
-            runner.term.try_draw(|f: &mut Frame| {
-                let area = f.area();
-                runner.model.view(f, area)
-            })?;
-            match runner.mainloop().await {
-                Err(err) => {
-                    runner.finalize();
-                    return Err(err);
+            if dirty && Instant::now() >= next_frame {
+                self.draw()?;
+                dirty = false;
+                next_frame = Instant::now() + frame_time;
+            }
+            let Some(msg) = self.wait_message(dirty.then_some(next_frame)).await? else {
+                continue;
+            };
+            match self.process(msg)? {
+                BreakDepth::ReadMoreEvents => (),
+                BreakDepth::DrawModel => dirty = true,
+                BreakDepth::EndProgram => return Ok(()),
+                BreakDepth::Suspend => {
+                    self.suspend().await?;
+                    dirty = true;
+                }
+                BreakDepth::Exec(cmd) => {
+                    self.exec(cmd).await?;
+                    dirty = true;
                 }
-                Ok(BreakDepth::EndProgram) => break,
-                Ok(_) => continue,
             }
         }
-        runner.finalize();
-        Ok(())
+    }
+    // Run the model on a screen that is already set up, leaving it set up
+    // for whatever runs next
+    pub async fn run_on(model: &'m mut M, screen: &'m mut Screen) -> Result<()> {
+        let mut model = Loader::new(model);
+        let mut runner = MainLooper::on_screen(&mut model, screen);
+        let result = match runner.init() {
+            Ok(()) => runner.run_loop().await,
+            Err(err) => Err(err),
+        };
+        let exited = runner.exit();
+        runner.finish();
+        result.and(exited)
+    }
+    pub async fn run(model: &'m mut M, options: RunOptions, on_start: impl FnOnce(ProgramHandle)) -> Result<()> {
+        let mut screen = Screen::new(options)?;
+        on_start(screen.handle());
+        let result = MainLooper::run_on(model, &mut screen).await;
+        screen.close();
+        result
+    }
+}
+
+impl<'m, M: Model> MainLooper<'m, M, TestBackend> {
+    // Wait for every running command to finish, holding on to what they
+    // send meanwhile so none of them blocks on a full channel
+    async fn wait_commands(&mut self) {
+        let tasks = self.tasks.clone();
+        let idle = tasks.idle();
+        tokio::pin!(idle);
+        loop {
+            tokio::select! {
+                _ = &mut idle => return,
+                Some(msg) = self.screen.rx.recv() => self.pending.push_back(msg),
+            }
+        }
+    }
+
+    // Let the commands started so far report back, then handle every
+    // message that doesn't need waiting for. Commands started while doing
+    // that are left running until the next settle.
+    async fn settle(&mut self) -> Result<BreakDepth> {
+        self.wait_commands().await;
+        loop {
+            match self.inner().await? {
+                BreakDepth::EndProgram => return Ok(BreakDepth::EndProgram),
+                BreakDepth::ReadMoreEvents => continue,
+                BreakDepth::Suspend => log::debug!("Ignoring suspend in headless mode"),
+                BreakDepth::Exec(cmd) => {
+                    log::debug!("Not running {cmd:?} in headless mode");
+                    self.pending.push_back(Message::ExecInteractiveExited(None));
+                }
+                BreakDepth::DrawModel => if self.pending.is_empty() && self.screen.rx.is_empty() {
+                    return Ok(BreakDepth::DrawModel);
+                },
+            }
+        }
+    }
+
+    fn snapshot(&mut self) -> Result<Buffer> {
+        self.draw()?;
+        Ok(self.screen.term.backend().buffer().clone())
+    }
+
+    // Drive the model with a scripted series of messages instead of a
+    // terminal, rendering a frame after init and after each message.
+    // Before each frame, the commands already running are waited for and
+    // their messages delivered, so timers really do take their time.
+    pub async fn run_headless(
+        model: &'m mut M,
+        backend: TestBackend,
+        script: impl IntoIterator<Item=Message>,
+    ) -> Result<Vec<Buffer>> {
+        let mut screen = Screen::with_terminal(Terminal::new(backend)?, Default::default());
+        let mut model = Loader::new(model);
+        let mut runner = MainLooper::on_screen(&mut model, &mut screen);
+        let result = runner.headless_loop(script).await;
+        let exited = runner.exit();
+        runner.stop_background();
+        result.and_then(|frames| exited.map(|()| frames))
+    }
+
+    async fn headless_loop(&mut self, script: impl IntoIterator<Item=Message>) -> Result<Vec<Buffer>> {
+        self.init()?;
+        let mut frames = Vec::new();
+        if self.settle().await? == BreakDepth::EndProgram {
+            return Ok(frames);
+        }
+        frames.push(self.snapshot()?);
+        for msg in script {
+            if let Message::Resize(width, height) = msg {
+                self.screen.term.backend_mut().resize(width as u16, height as u16);
+                self.screen.term.autoresize()?;
+            }
+            if self.process(msg)? == BreakDepth::EndProgram
+                || self.settle().await? == BreakDepth::EndProgram {
+                break;
+            }
+            frames.push(self.snapshot()?);
+        }
+        Ok(frames)
     }
 }
@@ -0,0 +1,287 @@
+// Matching search queries against list items
+use regex::{Regex, RegexBuilder};
+use std::cell::RefCell;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatchMode {
+    // The query appears anywhere in the text
+    #[default]
+    Substring,
+    // The text starts with the query
+    Prefix,
+    // The query's characters appear in order, best matches first
+    Fuzzy,
+    // The query is a regular expression found anywhere in the text
+    Regex,
+}
+
+// How well some text matched, and which of its characters did
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Match {
+    // Higher is better; only fuzzy matching gives different scores
+    pub score: i64,
+    // Indexes of the matched chars, in ascending order
+    pub positions: Vec<usize>,
+}
+
+// Fuzzy scoring, loosely after fzf: every matched char scores, more so at
+// the start of a word or right after the previous match, and skipping
+// chars between matches costs a little
+const SCORE_MATCH: i64 = 16;
+const BONUS_BOUNDARY: i64 = 8;
+const BONUS_CONSECUTIVE: i64 = 8;
+const PENALTY_GAP_START: i64 = 3;
+const PENALTY_GAP_EXTENSION: i64 = 1;
+// Marks a spot in the table where the query can't be matched
+const NO_SCORE: i64 = i64::MIN;
+
+thread_local! {
+    // The fuzzy scoring table, kept to be reused for the next text
+    static TABLE: RefCell<Vec<(i64, usize)>> = const { RefCell::new(Vec::new()) };
+}
+
+// Lowercased char by char, so positions still line up with the original
+fn lowercase(text: &str) -> Vec<char> {
+    text.chars().map(|c| c.to_lowercase().next().unwrap_or(c)).collect()
+}
+
+// Text to be searched, lowercased once so it can be matched against many
+// queries cheaply
+#[derive(Debug, Clone, Default)]
+pub struct SearchKey {
+    text: String,
+    lower: Vec<char>,
+}
+
+impl SearchKey {
+    pub fn new(text: impl Into<String>) -> Self {
+        let text = text.into();
+        let lower = lowercase(&text);
+        Self { text, lower }
+    }
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+// A query prepared for matching against many texts. Matching ignores case.
+#[derive(Debug, Clone, Default)]
+pub struct Matcher {
+    mode: MatchMode,
+    query: Vec<char>,
+    regex: Option<Regex>,
+}
+
+impl Matcher {
+    // An invalid regular expression is matched as plain text
+    pub fn new(mode: MatchMode, query: &str) -> Self {
+        let regex = match mode {
+            MatchMode::Regex => RegexBuilder::new(query)
+                .case_insensitive(true)
+                .build()
+                .or_else(|_| RegexBuilder::new(&regex::escape(query)).case_insensitive(true).build())
+                .ok(),
+            _ => None,
+        };
+        Self {
+            mode,
+            query: lowercase(query),
+            regex,
+        }
+    }
+
+    pub fn mode(&self) -> MatchMode {
+        self.mode
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.query.is_empty()
+    }
+
+    // Whether this only matches text the other matcher matched too, so
+    // that only its matches need checking again. True when a query has
+    // been typed onto, except for regular expressions.
+    pub fn narrows(&self, other: &Matcher) -> bool {
+        self.mode == other.mode
+            && self.mode != MatchMode::Regex
+            && self.query.starts_with(&other.query)
+    }
+
+    pub fn matches(&self, text: &str) -> Option<Match> {
+        self.matches_key(&SearchKey::new(text))
+    }
+
+    pub fn matches_key(&self, key: &SearchKey) -> Option<Match> {
+        if self.is_empty() {
+            return Some(Match::default());
+        }
+        match self.mode {
+            MatchMode::Regex => self.regex_match(&key.text),
+            MatchMode::Prefix => self.prefix_match(&key.lower),
+            MatchMode::Fuzzy => self.fuzzy_match(&key.lower),
+            MatchMode::Substring => self.substring_match(&key.lower),
+        }
+    }
+
+    fn prefix_match(&self, text: &[char]) -> Option<Match> {
+        text.starts_with(&self.query).then(|| Match {
+            score: 0,
+            positions: (0..self.query.len()).collect(),
+        })
+    }
+
+    fn substring_match(&self, text: &[char]) -> Option<Match> {
+        let start = text.windows(self.query.len()).position(|window| window == self.query)?;
+        Some(Match {
+            score: 0,
+            positions: (start..start + self.query.len()).collect(),
+        })
+    }
+
+    fn regex_match(&self, text: &str) -> Option<Match> {
+        let found = self.regex.as_ref()?.find(text)?;
+        let positions = text.char_indices()
+            .enumerate()
+            .filter(|(_, (byte, _))| found.range().contains(byte))
+            .map(|(i, _)| i)
+            .collect();
+        Some(Match { score: 0, positions })
+    }
+
+    fn fuzzy_match(&self, text: &[char]) -> Option<Match> {
+        let (m, n) = (self.query.len(), text.len());
+        // Most texts won't match at all, so check the query chars appear in
+        // order before doing any real work
+        let mut rest = self.query.iter().peekable();
+        for c in text {
+            rest.next_if_eq(&c);
+        }
+        if rest.peek().is_some() {
+            return None;
+        }
+        let bonus = |j: usize| {
+            let boundary = j == 0 || !text[j - 1].is_alphanumeric();
+            if boundary { BONUS_BOUNDARY } else { 0 }
+        };
+        TABLE.with_borrow_mut(|table| {
+            // table[i * n + j] holds the best score for the first i + 1
+            // query chars with the last of them matched at text[j], and
+            // where the one before it was matched
+            table.clear();
+            table.resize(m * n, (NO_SCORE, 0));
+            let score = |table: &[(i64, usize)], i: usize, j: usize| {
+                Some(table[i * n + j].0).filter(|&s| s != NO_SCORE)
+            };
+            for j in 0..n {
+                if text[j] == self.query[0] {
+                    table[j].0 = SCORE_MATCH + bonus(j);
+                }
+            }
+            for i in 1..m {
+                // Best score so far to continue from after a gap, and where
+                let mut gap: Option<(i64, usize)> = None;
+                for j in i..n {
+                    if j >= 2 && let Some(prev) = score(table, i - 1, j - 2) {
+                        let opened = prev - PENALTY_GAP_START;
+                        gap = match gap {
+                            Some((best, k)) if best - PENALTY_GAP_EXTENSION >= opened =>
+                                Some((best - PENALTY_GAP_EXTENSION, k)),
+                            _ => Some((opened, j - 2)),
+                        };
+                    } else if let Some((best, k)) = gap {
+                        gap = Some((best - PENALTY_GAP_EXTENSION, k));
+                    }
+                    if text[j] != self.query[i] {
+                        continue;
+                    }
+                    let adjacent = score(table, i - 1, j - 1).map(|prev| (prev + BONUS_CONSECUTIVE, j - 1));
+                    let best = match (adjacent, gap) {
+                        (Some(a), Some(g)) => Some(if a.0 >= g.0 { a } else { g }),
+                        (a, g) => a.or(g),
+                    };
+                    if let Some((prev, k)) = best {
+                        table[i * n + j] = (prev + SCORE_MATCH + bonus(j), k);
+                    }
+                }
+            }
+            let (end, best) = (0..n)
+                .filter_map(|j| score(table, m - 1, j).map(|s| (j, s)))
+                .max_by_key(|&(j, s)| (s, std::cmp::Reverse(j)))?;
+            let mut positions = vec![end; m];
+            for i in (1..m).rev() {
+                positions[i - 1] = table[i * n + positions[i]].1;
+            }
+            Some(Match { score: best, positions })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(mode: MatchMode, query: &str, text: &str) -> Option<i64> {
+        Matcher::new(mode, query).matches(text).map(|m| m.score)
+    }
+
+    fn positions(mode: MatchMode, query: &str, text: &str) -> Option<Vec<usize>> {
+        Matcher::new(mode, query).matches(text).map(|m| m.positions)
+    }
+
+    #[test]
+    fn fuzzy_ranks_tighter_matches_first() {
+        let ranked = ["ab", "a_b", "xab", "axxxb"]
+            .map(|text| score(MatchMode::Fuzzy, "ab", text).unwrap());
+        assert!(ranked.is_sorted_by(|a, b| a > b), "{ranked:?}");
+    }
+
+    #[test]
+    fn fuzzy_prefers_word_starts() {
+        assert_eq!(positions(MatchMode::Fuzzy, "usw", "US West"), Some(vec![0, 1, 3]));
+        assert_eq!(positions(MatchMode::Fuzzy, "fb", "fab foo_bar"), Some(vec![4, 8]));
+        assert!(score(MatchMode::Fuzzy, "usw", "US West") > score(MatchMode::Fuzzy, "usw", "Users Wiki"));
+    }
+
+    #[test]
+    fn fuzzy_needs_chars_in_order() {
+        assert_eq!(score(MatchMode::Fuzzy, "ba", "ab"), None);
+        assert_eq!(score(MatchMode::Fuzzy, "abc", "ab"), None);
+        assert_eq!(positions(MatchMode::Fuzzy, "ÉÉ", "été"), Some(vec![0, 2]));
+    }
+
+    #[test]
+    fn substring_and_prefix_ignore_case_and_count_chars() {
+        assert_eq!(positions(MatchMode::Substring, "ÉT", "café été"), Some(vec![5, 6]));
+        assert_eq!(positions(MatchMode::Substring, "x", "café"), None);
+        assert_eq!(positions(MatchMode::Prefix, "CAF", "Café"), Some(vec![0, 1, 2]));
+        assert_eq!(positions(MatchMode::Prefix, "afé", "Café"), None);
+    }
+
+    #[test]
+    fn regex_positions_count_chars() {
+        assert_eq!(positions(MatchMode::Regex, "t.", "ÉTÉ"), Some(vec![1, 2]));
+        assert_eq!(positions(MatchMode::Regex, "^b", "ab"), None);
+    }
+
+    #[test]
+    fn invalid_regex_matches_literally() {
+        assert_eq!(positions(MatchMode::Regex, "a(", "xA(y"), Some(vec![1, 2]));
+        assert_eq!(positions(MatchMode::Regex, "a(", "ab"), None);
+    }
+
+    #[test]
+    fn empty_query_matches_everything() {
+        for mode in [MatchMode::Substring, MatchMode::Prefix, MatchMode::Fuzzy, MatchMode::Regex] {
+            assert_eq!(Matcher::new(mode, "").matches("anything"), Some(Match::default()));
+        }
+    }
+
+    #[test]
+    fn longer_queries_narrow_except_regex() {
+        let fuzzy = |query| Matcher::new(MatchMode::Fuzzy, query);
+        assert!(fuzzy("ab").narrows(&fuzzy("a")));
+        assert!(!fuzzy("a").narrows(&fuzzy("ab")));
+        assert!(!Matcher::new(MatchMode::Prefix, "ab").narrows(&fuzzy("a")));
+        assert!(!Matcher::new(MatchMode::Regex, "ab").narrows(&Matcher::new(MatchMode::Regex, "a")));
+    }
+}
@@ -1,13 +1,44 @@
 use crate::error::*;
-use ratatui::crossterm::event::KeyEvent;
+use crate::panic::catch_async;
+use crate::shell::{ShellCommand, OutputStream};
+use crate::tasks::{Task, Tasks};
+use ratatui::crossterm::event::{KeyEvent, MouseEvent, MouseEventKind, MouseButton};
+use ratatui::layout::{Position, Rect};
+use std::any::Any;
+use std::fmt::Debug;
 use uuid::Uuid;
 use tokio::{
-    spawn,
     time::{Duration, sleep},
     process::Command,
     sync::mpsc::Sender,
 };
 
+// Application-defined payload carried by Message::Custom.
+// Implemented for any cloneable, debuggable type that can be sent between threads.
+pub trait CustomMessage: Any + Send + Debug {
+    fn clone_box(&self) -> Box<dyn CustomMessage>;
+    fn as_any(&self) -> &dyn Any;
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<T: Any + Send + Clone + Debug> CustomMessage for T {
+    fn clone_box(&self) -> Box<dyn CustomMessage> {
+        Box::new(self.clone())
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+impl Clone for Box<dyn CustomMessage> {
+    fn clone(&self) -> Self {
+        (**self).clone_box()
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub enum Message {
 
@@ -21,6 +52,17 @@ pub enum Message {
     // Signals a quit for a sub-screen or dialog
     Dismiss,
 
+    // Restore the terminal and stop the process, as with Ctrl-Z in a shell.
+    // The screen is set up and redrawn again when the process is resumed.
+    Suspend,
+
+    // Hand the terminal over to an interactive program such as $EDITOR,
+    // then take it back and reply with ExecInteractiveExited and the
+    // program's exit status (None if it was killed by a signal or failed
+    // to start).
+    ExecInteractive(Vec<String>),
+    ExecInteractiveExited(Option<i32>),
+
     // Simple affirmative or negative response
     Yes,
     No,
@@ -28,6 +70,9 @@ pub enum Message {
     // Signals the application to redraw the UI, jumping the event queue
     Redraw,
 
+    // Clear the terminal and redraw everything from scratch
+    Repaint,
+
     // An error occurred
     Error(Error),
 
@@ -43,8 +88,26 @@ pub enum Message {
     // A key was pressed
     KeyPress(KeyEvent),
 
-    // A choice was made from a list of options
+    // The mouse was clicked, moved or scrolled
+    // Only sent when mouse capture is enabled in the RunOptions
+    Mouse(MouseEvent),
+
+    // Text was pasted into the terminal
+    // Only sent when bracketed paste is enabled in the RunOptions
+    Paste(String),
+
+    // The terminal window gained (true) or lost (false) focus
+    // Only sent when focus reporting is enabled in the RunOptions
+    TerminalFocus(bool),
+
+    // A choice was made from a list of options. A ListView sends the
+    // item's index in its full list of items, whatever the search shows;
+    // look it up with ListView::get. This is a breaking change: it used
+    // to be the row in the filtered list, for ListView::filtered_get.
     Choice(usize),
+    // Several options were chosen at once, in list order, indexed as for
+    // Choice
+    Choices(Vec<usize>),
 
     // Timer tick and tock messages
     // Use the Message::tick(duration) function to create a tick message
@@ -52,6 +115,19 @@ pub enum Message {
     Tik(Uuid, Duration),
     Tok(Uuid),
 
+    // Run any future, delivering the message it resolves to
+    // Use the Message::task(future) function to create one.
+    Task(Task),
+
+    // Stop the timer, shell command, task or sequence with this id.
+    // The ids are the ones returned by Message::tick, Message::shell
+    // and Message::task.
+    // A cancelled command sends no further messages.
+    Cancel(Uuid),
+
+    // A file watched by a subscription changed
+    FileChanged(std::path::PathBuf),
+
     // Composite messages
     // Execute all messages in parallel
     Batch(Vec<Message>),
@@ -59,8 +135,24 @@ pub enum Message {
     Sequence(Vec<Message>),
 
     // Shell command input
+    // The command has no id to cancel it by, though it is still stopped
+    // along with the model that ran it; use StreamShellCommand for one
+    // that can be cancelled.
     ShellCommand(Vec<String>),
     ShellCommandOutput(String),
+
+    // Streaming shell command
+    // Use the Message::shell(command) function to run a command that
+    // reports each line of output as it is written and then its exit
+    // status (None if it was killed by a signal or failed to start).
+    StreamShellCommand(Box<ShellCommand>),
+    ShellCommandLine { id: Uuid, stream: OutputStream, line: String },
+    ShellCommandExited { id: Uuid, status: Option<i32> },
+
+    // An application-defined event
+    // Use Message::custom(value) to create one and downcast_ref or
+    // downcast to get the value back out.
+    Custom(Box<dyn CustomMessage>),
 }
 impl Message {
     pub fn error(err: Error) -> Message {
@@ -72,11 +164,61 @@ impl Message {
     pub fn choose(choice: usize) -> Message {
         Message::Choice(choice)
     }
+    pub fn choose_many(choices: impl IntoIterator<Item=usize>) -> Message {
+        Message::Choices(choices.into_iter().collect())
+    }
     pub fn tick(duration: Duration) -> (Uuid, Message) {
         let id = Uuid::new_v4();
         (
             id,
-            Message::Tik(id.clone(), duration)
+            Message::Tik(id, duration)
+        )
+    }
+    // True if this is a left click landing inside the area
+    pub fn clicked_in(&self, area: Rect) -> bool {
+        match self {
+            Message::Mouse(MouseEvent{kind: MouseEventKind::Down(MouseButton::Left), column, row, ..}) =>
+                area.contains(Position::new(*column, *row)),
+            _ => false,
+        }
+    }
+    pub fn custom<T: CustomMessage>(value: T) -> Message {
+        Message::Custom(Box::new(value))
+    }
+    // True if this is a custom message holding a T
+    pub fn is<T: Any>(&self) -> bool {
+        self.downcast_ref::<T>().is_some()
+    }
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        match self {
+            Message::Custom(value) => (**value).as_any().downcast_ref(),
+            _ => None,
+        }
+    }
+    // Take the T out of a custom message, or get the message back unchanged
+    pub fn downcast<T: Any>(self) -> std::result::Result<T, Message> {
+        if !self.is::<T>() {
+            return Err(self);
+        }
+        match self {
+            Message::Custom(value) => match value.into_any().downcast() {
+                Ok(value) => Ok(*value),
+                Err(_) => unreachable!("type was checked before downcasting"),
+            },
+            _ => unreachable!("only custom messages hold a value"),
+        }
+    }
+    pub fn shell(command: ShellCommand) -> (Uuid, Message) {
+        (
+            command.id(),
+            Message::StreamShellCommand(Box::new(command))
+        )
+    }
+    pub fn task(future: impl std::future::Future<Output=Message> + Send + 'static) -> (Uuid, Message) {
+        let task = Task::new(future);
+        (
+            task.id(),
+            Message::Task(task)
         )
     }
     pub fn or(self, next: Message) -> Message {
@@ -117,7 +259,26 @@ impl Message {
             _ => Message::Sequence(vec![self, next]),
         }
     }
-    async fn execute(self) -> Option<Message> {
+    // Whether the message needs to be executed rather than delivered
+    pub fn is_command(&self) -> bool {
+        match self {
+            Message::Tik(..) | Message::ShellCommand(_) | Message::StreamShellCommand(_)
+                | Message::Task(_) | Message::Cancel(_) => true,
+            Message::Batch(msgs) | Message::Sequence(msgs) => msgs.iter().any(Message::is_command),
+            _ => false,
+        }
+    }
+    // The ids a running command can be cancelled by
+    fn command_ids(&self) -> Vec<Uuid> {
+        match self {
+            Message::Tik(id, _) => vec![*id],
+            Message::StreamShellCommand(cmd) => vec![cmd.id()],
+            Message::Task(task) => vec![task.id()],
+            Message::Batch(msgs) | Message::Sequence(msgs) => msgs.iter().flat_map(Message::command_ids).collect(),
+            _ => Vec::new(),
+        }
+    }
+    async fn execute(self, tx: &Sender<Message>) -> Option<Message> {
         match self {
             Message::Noop => None,
             Message::Tik(id, duration) => {
@@ -127,6 +288,7 @@ impl Message {
             Message::ShellCommand(cmd) => {
                 let output = Command::new(&cmd[0])
                     .args(&cmd[1..])
+                    .kill_on_drop(true)
                     .output()
                     .await;
                 match output {
@@ -137,34 +299,50 @@ impl Message {
                     Err(e) => Some(Message::Error(e.into())),
                 }
             }
+            Message::StreamShellCommand(cmd) => Some(cmd.execute(tx).await),
+            Message::Task(task) => task.execute().await,
             _ => Some(self),
         }
     }
-    pub fn spawn(self, tx: Sender<Message>) {
+    pub fn spawn(self, tx: Sender<Message>, tasks: &Tasks) {
         match self {
+            Message::Cancel(id) => {
+                tasks.cancel(id);
+            },
             Message::Sequence(msgs) => {
-                spawn(async move {
+                let ids = msgs.iter().flat_map(Message::command_ids).collect();
+                tasks.spawn(ids, async move {
                     for cmd in msgs {
-                        if let Some(msg) = cmd.execute().await {
-                            let _ = tx.send(msg).await;
+                        if !cmd.run(&tx).await {
+                            break;
                         }
                     }
                 });
             },
             Message::Batch(msgs) => {
                 for msg in msgs {
-                    msg.spawn(tx.clone())
+                    msg.spawn(tx.clone(), tasks)
                 }
             },
             _ => {
-                spawn(async move {
-                    if let Some(msg) = self.execute().await {
-                        let _ = tx.send(msg).await;
-                    };
+                tasks.spawn(self.command_ids(), async move {
+                    self.run(&tx).await;
                 });
             }
         };
     }
+    // Execute the command and send back what it resolves to, or a fatal
+    // error if it panicked, returning false in that case
+    async fn run(self, tx: &Sender<Message>) -> bool {
+        let (msg, ok) = match catch_async(self.execute(tx)).await {
+            Ok(msg) => (msg, true),
+            Err(e) => (Some(Message::ErrorFatal(e)), false),
+        };
+        if let Some(msg) = msg {
+            let _ = tx.send(msg).await;
+        }
+        ok
+    }
     pub fn into_option(self) -> Option<Message> {
         match self {
             Message::Noop => None,
@@ -0,0 +1,122 @@
+// Settings for how a Runner drives the terminal
+use crate::message::Message;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::path::PathBuf;
+
+// Keys the runner handles itself before the model sees them.
+// Set a binding to None to pass that key through to the model instead.
+#[derive(Debug, Clone)]
+pub struct GlobalKeys {
+    // Sends Message::Quit; Ctrl-C by default
+    pub quit: Option<KeyEvent>,
+    // Sends Message::Suspend; Ctrl-Z by default
+    pub suspend: Option<KeyEvent>,
+    // Sends Message::Repaint; unbound by default
+    pub repaint: Option<KeyEvent>,
+}
+
+impl Default for GlobalKeys {
+    fn default() -> Self {
+        Self {
+            quit: Some(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            suspend: Some(KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL)),
+            repaint: None,
+        }
+    }
+}
+
+impl GlobalKeys {
+    // The message a key press is bound to, if any
+    pub fn message_for(&self, key: &KeyEvent) -> Option<Message> {
+        let matches = |binding: &Option<KeyEvent>| match binding {
+            Some(binding) => binding.code == key.code && binding.modifiers == key.modifiers,
+            None => false,
+        };
+        if matches(&self.quit) {
+            Some(Message::Quit)
+        } else if matches(&self.suspend) {
+            Some(Message::Suspend)
+        } else if matches(&self.repaint) {
+            Some(Message::Repaint)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RunOptions {
+    // Report mouse clicks, scrolling and movement as Message::Mouse
+    pub mouse_capture: bool,
+    // Deliver pasted text as a single Message::Paste instead of keystrokes
+    pub bracketed_paste: bool,
+    // Report the terminal window gaining and losing focus as
+    // Message::TerminalFocus
+    pub focus_reporting: bool,
+    // Draw in this many lines below the cursor instead of taking over the
+    // whole screen, leaving the last frame in the scrollback on exit
+    pub inline_height: Option<u16>,
+    pub keys: GlobalKeys,
+    // Most frames to draw per second, however often redraws are
+    // requested; 0 for no limit
+    pub max_fps: u32,
+    // Save every event from the terminal to this file as JSON lines,
+    // to be replayed with Runner::replay
+    pub record: Option<PathBuf>,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            mouse_capture: false,
+            bracketed_paste: true,
+            focus_reporting: false,
+            inline_height: None,
+            keys: GlobalKeys::default(),
+            max_fps: 60,
+            record: None,
+        }
+    }
+}
+
+impl RunOptions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    pub fn with_mouse_capture(mut self, enabled: bool) -> Self {
+        self.mouse_capture = enabled;
+        self
+    }
+    pub fn with_bracketed_paste(mut self, enabled: bool) -> Self {
+        self.bracketed_paste = enabled;
+        self
+    }
+    pub fn with_focus_reporting(mut self, enabled: bool) -> Self {
+        self.focus_reporting = enabled;
+        self
+    }
+    pub fn with_inline_viewport(mut self, height: u16) -> Self {
+        self.inline_height = Some(height);
+        self
+    }
+    pub fn with_max_fps(mut self, fps: u32) -> Self {
+        self.max_fps = fps;
+        self
+    }
+    pub fn with_recording(mut self, path: impl Into<PathBuf>) -> Self {
+        self.record = Some(path.into());
+        self
+    }
+    pub fn with_quit_key(mut self, key: Option<KeyEvent>) -> Self {
+        self.keys.quit = key;
+        self
+    }
+    pub fn with_suspend_key(mut self, key: Option<KeyEvent>) -> Self {
+        self.keys.suspend = key;
+        self
+    }
+    pub fn with_repaint_key(mut self, key: Option<KeyEvent>) -> Self {
+        self.keys.repaint = key;
+        self
+    }
+}
@@ -0,0 +1,72 @@
+use crate::error::*;
+use std::backtrace::Backtrace;
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::pin::Pin;
+use std::sync::Once;
+use std::task::{Context, Poll};
+
+thread_local! {
+    // Set while a model is being called through catch on this thread
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+    static BACKTRACE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+static INSTALL_HOOK: Once = Once::new();
+
+// Record the backtrace of panics we are going to catch instead of printing
+// them over the UI; leave any other panic to the previous hook
+fn install_hook() {
+    INSTALL_HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if CATCHING.get() {
+                BACKTRACE.set(Some(Backtrace::force_capture().to_string()));
+            } else {
+                previous(info);
+            }
+        }));
+    });
+}
+
+// Run f, turning a panic into Error::Panic
+pub(crate) fn catch<T>(f: impl FnOnce() -> T) -> Result<T> {
+    install_hook();
+    let was_catching = CATCHING.replace(true);
+    let result = catch_unwind(AssertUnwindSafe(f));
+    CATCHING.set(was_catching);
+    result.map_err(|payload| {
+        let message = match payload.downcast_ref::<&str>() {
+            Some(message) => message.to_string(),
+            None => match payload.downcast_ref::<String>() {
+                Some(message) => message.clone(),
+                None => "unknown panic".to_string(),
+            },
+        };
+        let backtrace = BACKTRACE.take().unwrap_or_default();
+        log::error!("Panic: {message}\n{backtrace}");
+        Error::Panic { message, backtrace }
+    })
+}
+
+// Run the future, turning a panic while polling it into Error::Panic.
+// Polling through catch also keeps a panic on a runtime worker from
+// reaching the terminal's hook while the UI is still up.
+pub(crate) async fn catch_async<T>(future: impl Future<Output=T>) -> Result<T> {
+    CatchFuture(Box::pin(future)).await
+}
+
+struct CatchFuture<F>(Pin<Box<F>>);
+
+impl<F: Future> Future for CatchFuture<F> {
+    type Output = Result<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match catch(|| self.0.as_mut().poll(cx)) {
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+}
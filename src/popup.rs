@@ -2,14 +2,24 @@ use crate::prelude_internal::*;
 use ratatui::{
     widgets::Borders,
     layout::Margin,
+    crossterm::event::{MouseEvent, MouseEventKind},
 };
 
 pub struct Popup(String);
 impl Popup {
+    pub fn new<M: std::fmt::Display>(message: M) -> Self {
+        Popup(format!("{message}"))
+    }
     pub async fn show<M: std::fmt::Display>(message: M) -> Result<()> {
-        let mut p = Popup(format!("{message}"));
+        let mut p = Popup::new(message);
         p.run().await
     }
+    // Show the message on a program that is already running, keeping
+    // the terminal set up for whatever comes next
+    pub async fn show_in<M: std::fmt::Display>(program: &mut Program, message: M) -> Result<()> {
+        let mut p = Popup::new(message);
+        program.run(&mut p).await
+    }
 }
 
 impl Runner for Popup {}
@@ -19,6 +29,8 @@ impl Model for Popup {
         match msg {
         Message::KeyPress(_)
             => Message::Quit,
+        Message::Mouse(MouseEvent{kind: MouseEventKind::Down(_), ..})
+            => Message::Quit,
             _ =>Message::Noop,
         }
     }
@@ -2,12 +2,20 @@
 pub use crate::{
     Model,
     Runner,
+    RunnerWithOutput,
     error::{Result,Error},
-    message::Message,
+    message::{Message, CustomMessage},
+    options::{RunOptions, GlobalKeys},
+    shell::{ShellCommand, OutputStream},
+    tasks::Task,
+    subscription::{Subscription, SharedReceiver, shared_receiver},
+    program::{Program, ProgramHandle},
     input_control::InputControl,
     button::{Button,ButtonBar},
     spinner::Spinner,
-    listview::ListView,
+    listview::{ListView, ItemState},
+    list_source::{ListSource, PagedSource, StreamedSource},
+    matcher::MatchMode,
     log_viewer::{LogViewer, init_logging},
     popup::Popup,
     tabcontroller::TabController,
@@ -1,8 +1,12 @@
 pub use crate::{
     Model,
     Runner,
+    RunnerWithOutput,
     error::{Error,Result},
     message::Message,
+    options::RunOptions,
+    program::Program,
+    tasks::Tasks,
     styles::{STYLES,FocusState},
 };
 pub use ratatui::{
@@ -0,0 +1,88 @@
+use crate::prelude_internal::*;
+use crate::mainlooper::{MainLooper, Screen};
+use tokio::sync::mpsc;
+
+// Owns the terminal and the event reader so that several models can be
+// run one after another without handing the terminal back in between.
+// Input typed while one model is finishing goes to the next one. The
+// terminal is restored when the program is closed or dropped.
+pub struct Program {
+    screen: Screen,
+}
+
+impl Program {
+    pub fn new(options: RunOptions) -> Result<Self> {
+        Ok(Self { screen: Screen::new(options)? })
+    }
+
+    // Reaches whichever model is running at the time
+    pub fn handle(&self) -> ProgramHandle {
+        self.screen.handle()
+    }
+
+    // Run the model until it quits, leaving the terminal set up
+    pub async fn run<R: Runner>(&mut self, model: &mut R) -> Result<()> {
+        MainLooper::run_on(model, &mut self.screen).await
+    }
+
+    // Like run, but return the model's output
+    pub async fn run_for_output<R: RunnerWithOutput>(&mut self, model: &mut R) -> Result<R::Output> {
+        self.run(model).await?;
+        Ok(model.output())
+    }
+
+    pub fn close(mut self) {
+        self.screen.close();
+    }
+}
+
+impl Drop for Program {
+    fn drop(&mut self) {
+        self.screen.close();
+    }
+}
+
+// A cloneable way to reach a running program from other tasks or threads.
+// Messages sent through it are handled as if they had been returned from
+// the model's update: commands are run and anything else is delivered to
+// the model.
+#[derive(Debug, Clone)]
+pub struct ProgramHandle {
+    tx: mpsc::Sender<Message>,
+}
+
+impl ProgramHandle {
+    pub(crate) fn new(tx: mpsc::Sender<Message>) -> Self {
+        Self { tx }
+    }
+
+    pub async fn send(&self, msg: Message) -> Result<()> {
+        self.tx.send(msg).await.map_err(|_| Error::Error("program has exited"))
+    }
+
+    // For use from threads outside the async runtime; panics if called
+    // from within it
+    pub fn blocking_send(&self, msg: Message) -> Result<()> {
+        self.tx.blocking_send(msg).map_err(|_| Error::Error("program has exited"))
+    }
+
+    // Send without waiting, failing if the program is busy or has exited
+    pub fn try_send(&self, msg: Message) -> Result<()> {
+        self.tx.try_send(msg).map_err(|err| match err {
+            mpsc::error::TrySendError::Full(_) => Error::Error("program is busy"),
+            mpsc::error::TrySendError::Closed(_) => Error::Error("program has exited"),
+        })
+    }
+
+    pub async fn redraw(&self) -> Result<()> {
+        self.send(Message::Redraw).await
+    }
+
+    pub async fn quit(&self) -> Result<()> {
+        self.send(Message::Quit).await
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+}
@@ -0,0 +1,136 @@
+use crate::prelude_internal::*;
+use ratatui::{
+    backend::TestBackend,
+    crossterm::event::{KeyEvent, MouseEvent},
+};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Instant;
+
+// The messages that come from the terminal, in a form that can be saved
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecordedEvent {
+    Key(KeyEvent),
+    Mouse(MouseEvent),
+    Paste(String),
+    Resize(u16, u16),
+    TerminalFocus(bool),
+    Quit,
+    Suspend,
+    Repaint,
+}
+
+impl RecordedEvent {
+    // None for messages that don't come from the terminal
+    pub fn from_message(msg: &Message) -> Option<Self> {
+        Some(match msg {
+            Message::KeyPress(key) => Self::Key(*key),
+            Message::Mouse(mouse) => Self::Mouse(*mouse),
+            Message::Paste(text) => Self::Paste(text.clone()),
+            Message::Resize(width, height) => Self::Resize(*width as u16, *height as u16),
+            Message::TerminalFocus(focused) => Self::TerminalFocus(*focused),
+            Message::Quit => Self::Quit,
+            Message::Suspend => Self::Suspend,
+            Message::Repaint => Self::Repaint,
+            _ => return None,
+        })
+    }
+}
+
+impl From<RecordedEvent> for Message {
+    fn from(event: RecordedEvent) -> Self {
+        match event {
+            RecordedEvent::Key(key) => Message::KeyPress(key),
+            RecordedEvent::Mouse(mouse) => Message::Mouse(mouse),
+            RecordedEvent::Paste(text) => Message::Paste(text),
+            RecordedEvent::Resize(width, height) => Message::Resize(width as usize, height as usize),
+            RecordedEvent::TerminalFocus(focused) => Message::TerminalFocus(focused),
+            RecordedEvent::Quit => Message::Quit,
+            RecordedEvent::Suspend => Message::Suspend,
+            RecordedEvent::Repaint => Message::Repaint,
+        }
+    }
+}
+
+// One line of a recording
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedEntry {
+    // Milliseconds since the recording started
+    pub at_ms: u64,
+    // Terminal size when the event arrived
+    pub width: u16,
+    pub height: u16,
+    pub event: RecordedEvent,
+}
+
+// Appends terminal events to a JSON-lines file
+#[derive(Debug)]
+pub(crate) struct Recorder {
+    started: Instant,
+    out: Mutex<BufWriter<File>>,
+}
+
+impl Recorder {
+    pub fn create(path: &Path) -> Result<Self> {
+        Ok(Self {
+            started: Instant::now(),
+            out: Mutex::new(BufWriter::new(File::create(path)?)),
+        })
+    }
+
+    pub fn record(&self, msg: &Message) {
+        let Some(event) = RecordedEvent::from_message(msg) else {
+            return;
+        };
+        let (width, height) = ratatui::crossterm::terminal::size().unwrap_or_default();
+        let entry = RecordedEntry {
+            at_ms: self.started.elapsed().as_millis() as u64,
+            width,
+            height,
+            event,
+        };
+        let mut out = self.out.lock().unwrap();
+        // Flush every line so a crash still leaves a usable recording
+        let written = serde_json::to_writer(&mut *out, &entry)
+            .map_err(Error::from)
+            .and_then(|_| Ok(writeln!(out)?))
+            .and_then(|_| Ok(out.flush()?));
+        if let Err(err) = written {
+            log::warn!("Error recording event: {err}");
+        }
+    }
+}
+
+pub fn load(path: impl AsRef<Path>) -> Result<Vec<RecordedEntry>> {
+    let mut entries = Vec::new();
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            entries.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(entries)
+}
+
+// A backend the size the terminal was at the start of the recording, and
+// the messages to replay into it, with resizes wherever the size changed
+pub fn to_script(entries: &[RecordedEntry]) -> (TestBackend, Vec<Message>) {
+    let (mut width, mut height) = match entries.first() {
+        Some(entry) => (entry.width, entry.height),
+        None => (80, 24),
+    };
+    let backend = TestBackend::new(width, height);
+    let mut script = Vec::new();
+    for entry in entries {
+        let resized = matches!(entry.event, RecordedEvent::Resize(..));
+        if !resized && (entry.width, entry.height) != (width, height) {
+            script.push(Message::Resize(entry.width as usize, entry.height as usize));
+        }
+        (width, height) = (entry.width, entry.height);
+        script.push(entry.event.clone().into());
+    }
+    (backend, script)
+}
@@ -0,0 +1,140 @@
+use crate::prelude_internal::*;
+use std::path::PathBuf;
+use std::process::Stdio;
+use uuid::Uuid;
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncWriteExt, BufReader},
+    process::Command,
+    sync::mpsc::Sender,
+};
+
+// Which output of a shell command a line came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+// A command whose output is streamed back line by line.
+// Use Message::shell(command) to run it; every line arrives as a
+// Message::ShellCommandLine and the exit status as a Message::ShellCommandExited,
+// all tagged with the command's id.
+#[derive(Debug, Clone)]
+pub struct ShellCommand {
+    id: Uuid,
+    program: String,
+    args: Vec<String>,
+    stdin: Option<String>,
+    current_dir: Option<PathBuf>,
+    env: Vec<(String, String)>,
+}
+
+impl ShellCommand {
+    pub fn new(program: impl Into<String>, args: impl IntoIterator<Item=impl Into<String>>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            program: program.into(),
+            args: args.into_iter().map(Into::into).collect(),
+            stdin: None,
+            current_dir: None,
+            env: Vec::new(),
+        }
+    }
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+    // Written to the command's standard input, which is then closed
+    pub fn with_stdin(mut self, input: impl Into<String>) -> Self {
+        self.stdin = Some(input.into());
+        self
+    }
+    pub fn with_current_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.current_dir = Some(dir.into());
+        self
+    }
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    async fn forward_lines(
+        id: Uuid,
+        stream: OutputStream,
+        mut reader: impl AsyncBufRead + Unpin,
+        tx: &Sender<Message>,
+    ) {
+        // Read raw bytes rather than lines, so that output which isn't
+        // valid UTF-8 is shown as best it can be instead of ending the stream
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            match reader.read_until(b'\n', &mut buf).await {
+                Ok(0) => return,
+                Ok(_) => {
+                    let line = buf.strip_suffix(b"\n").unwrap_or(&buf);
+                    let line = line.strip_suffix(b"\r").unwrap_or(line);
+                    let line = String::from_utf8_lossy(line).into_owned();
+                    let _ = tx.send(Message::ShellCommandLine { id, stream, line }).await;
+                }
+                Err(e) => {
+                    let _ = tx.send(Message::Error(e.into())).await;
+                    return;
+                }
+            }
+        }
+    }
+
+    // Run to completion, sending output lines as they arrive, and return
+    // the exit message
+    pub(crate) async fn execute(self, tx: &Sender<Message>) -> Message {
+        let id = self.id;
+        let mut command = Command::new(&self.program);
+        command.args(&self.args)
+            .envs(self.env)
+            .stdin(if self.stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(dir) = self.current_dir {
+            command.current_dir(dir);
+        }
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                let _ = tx.send(Message::Error(e.into())).await;
+                return Message::ShellCommandExited { id, status: None };
+            }
+        };
+        let stdin = child.stdin.take();
+        let input = self.stdin;
+        let write_stdin = async move {
+            // Dropping stdin closes it so the command sees end of input
+            if let (Some(mut stdin), Some(input)) = (stdin, input)
+                && let Err(e) = stdin.write_all(input.as_bytes()).await {
+                log::warn!("Error writing to stdin of {id}: {e}");
+            }
+        };
+        let stdout = child.stdout.take().map(BufReader::new);
+        let stderr = child.stderr.take().map(BufReader::new);
+        tokio::join!(
+            write_stdin,
+            async {
+                if let Some(stdout) = stdout {
+                    Self::forward_lines(id, OutputStream::Stdout, stdout, tx).await;
+                }
+            },
+            async {
+                if let Some(stderr) = stderr {
+                    Self::forward_lines(id, OutputStream::Stderr, stderr, tx).await;
+                }
+            },
+        );
+        match child.wait().await {
+            Ok(status) => Message::ShellCommandExited { id, status: status.code() },
+            Err(e) => {
+                let _ = tx.send(Message::Error(e.into())).await;
+                Message::ShellCommandExited { id, status: None }
+            }
+        }
+    }
+}
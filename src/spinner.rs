@@ -6,6 +6,8 @@ pub struct Spinner {
     chars: Vec<char>,
     index: usize,
     id: Uuid,
+    // Stopped while the terminal is in the background
+    paused: bool,
 }
 
 impl Model for Spinner {
@@ -18,7 +20,7 @@ impl Model for Spinner {
     fn update(&mut self, msg: Message) -> Message {
         match msg {
             Message::Tok(id) => {
-                if id == self.id {
+                if id == self.id && !self.paused {
                     self.index = (self.index + 1) % self.chars.len();
                     let (new_id, tik) = Message::tick(tokio::time::Duration::from_millis(100));
                     self.id = new_id;
@@ -27,6 +29,16 @@ impl Model for Spinner {
                     Message::Noop
                 }
             }
+            Message::TerminalFocus(false) if !self.paused => {
+                self.paused = true;
+                Message::Cancel(self.id)
+            }
+            Message::TerminalFocus(true) if self.paused => {
+                self.paused = false;
+                let (new_id, tik) = Message::tick(tokio::time::Duration::from_millis(100));
+                self.id = new_id;
+                Message::Redraw.and(tik)
+            }
             _ => Message::Noop,
         }
     }
@@ -58,6 +70,7 @@ impl Spinner {
             chars: chars.into_iter().collect(),
             index: 0,
             id: Uuid::new_v4(),
+            paused: false,
         }
     }
     pub fn circles() -> Self {
@@ -77,6 +90,7 @@ impl Default for Spinner {
             chars: vec!['|', '/', '-', '\\'],
             index: 0,
             id: Uuid::new_v4(),
+            paused: false,
         }
     }
 }
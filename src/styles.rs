@@ -6,6 +6,7 @@ use ratatui::widgets::{
 };
 use ratatui::Frame;
 use ratatui::layout::Rect;
+use ratatui::style::Modifier;
 
 #[derive(Debug, Default, Clone)]
 pub struct Style{
@@ -13,6 +14,8 @@ pub struct Style{
     pub highlight: ratatui::style::Style,
     pub highlight_symbol: &'static str,
     pub highlight_spacing: HighlightSpacing,
+    // The characters of list items that match a search
+    pub matched: ratatui::style::Style,
 }
 
 #[derive(Debug, Clone)]
@@ -57,6 +60,7 @@ impl Default for Styles {
                     highlight: ratatui::style::Style::default().bg(SKY.c700).fg(SKY.c950),
                     highlight_symbol: "➡︎ ",
                     highlight_spacing: HighlightSpacing::Always,
+                    matched: ratatui::style::Style::default().fg(ORANGE.c700).add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
             },
             focus: Style {
                 block: Block::default()
@@ -67,6 +71,7 @@ impl Default for Styles {
                     highlight: ratatui::style::Style::default().bg(ORANGE.c600).fg(SKY.c50),
                     highlight_symbol: "➡︎ ",
                     highlight_spacing: HighlightSpacing::Always,
+                    matched: ratatui::style::Style::default().fg(ORANGE.c700).add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
             },
             error: Style {
                 block: Block::default()
@@ -77,6 +82,7 @@ impl Default for Styles {
                     highlight: ratatui::style::Style::default().bg(ratatui::style::Color::Red).fg(ratatui::style::Color::White),
                     highlight_symbol: "‼ ",
                     highlight_spacing: HighlightSpacing::Always,
+                    matched: ratatui::style::Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
             },
         }
     }
@@ -0,0 +1,143 @@
+use crate::panic::catch_async;
+use crate::prelude_internal::*;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::{
+    sync::{Mutex, mpsc},
+    task::AbortHandle,
+    time::{Duration, Instant, MissedTickBehavior, interval_at, sleep},
+};
+
+// A receiver that can be handed out by Model::subscriptions on every
+// update while only one subscription at a time reads from it
+pub type SharedReceiver = Arc<Mutex<mpsc::Receiver<Message>>>;
+
+pub fn shared_receiver(rx: mpsc::Receiver<Message>) -> SharedReceiver {
+    Arc::new(Mutex::new(rx))
+}
+
+#[derive(Debug, Clone)]
+enum Source {
+    Interval(Duration, Message),
+    Receiver(SharedReceiver),
+    File(PathBuf, Duration),
+}
+
+// A recurring source of messages returned from Model::subscriptions.
+// The runner starts a subscription the first time its key appears and
+// stops it once the key is no longer returned, though messages it already
+// sent may still be delivered. Changing the settings of a subscription does
+// nothing unless its key changes too.
+#[derive(Debug, Clone)]
+pub struct Subscription {
+    key: String,
+    source: Source,
+}
+
+impl Subscription {
+    // Send the message every period, starting one period from now
+    pub fn interval(key: impl Into<String>, period: Duration, message: Message) -> Self {
+        Self {
+            key: key.into(),
+            source: Source::Interval(period, message),
+        }
+    }
+    // Forward every message sent on the channel
+    pub fn receiver(key: impl Into<String>, rx: SharedReceiver) -> Self {
+        Self {
+            key: key.into(),
+            source: Source::Receiver(rx),
+        }
+    }
+    // Send Message::FileChanged when the file's size or modification time
+    // changes, checking every poll period
+    pub fn file(key: impl Into<String>, path: impl Into<PathBuf>, poll: Duration) -> Self {
+        Self {
+            key: key.into(),
+            source: Source::File(path.into(), poll),
+        }
+    }
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    async fn run(self, tx: mpsc::Sender<Message>) {
+        match self.source {
+            Source::Interval(period, message) => {
+                let mut ticks = interval_at(Instant::now() + period, period);
+                ticks.set_missed_tick_behavior(MissedTickBehavior::Skip);
+                loop {
+                    ticks.tick().await;
+                    if tx.send(message.clone()).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            Source::Receiver(rx) => {
+                let mut rx = rx.lock().await;
+                while let Some(msg) = rx.recv().await {
+                    if tx.send(msg).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            Source::File(path, poll) => {
+                let stat = |path: &PathBuf| -> Option<(u64, SystemTime)> {
+                    let meta = std::fs::metadata(path).ok()?;
+                    Some((meta.len(), meta.modified().ok()?))
+                };
+                let mut last = stat(&path);
+                loop {
+                    sleep(poll).await;
+                    let current = stat(&path);
+                    if current != last {
+                        last = current;
+                        if tx.send(Message::FileChanged(path.clone())).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// The subscriptions currently running, by key
+#[derive(Debug, Default)]
+pub(crate) struct Subscriptions {
+    running: HashMap<String, AbortHandle>,
+}
+
+impl Subscriptions {
+    // Start and stop subscriptions to match the wanted ones
+    pub fn sync(&mut self, wanted: Vec<Subscription>, tx: &mpsc::Sender<Message>) {
+        let keys: Vec<&str> = wanted.iter().map(Subscription::key).collect();
+        self.running.retain(|key, handle| {
+            let keep = keys.contains(&key.as_str());
+            if !keep {
+                handle.abort();
+            }
+            keep
+        });
+        for sub in wanted {
+            if !self.running.contains_key(&sub.key) {
+                let key = sub.key.clone();
+                let tx = tx.clone();
+                let handle = tokio::spawn(async move {
+                    if let Err(e) = catch_async(sub.run(tx.clone())).await {
+                        let _ = tx.send(Message::ErrorFatal(e)).await;
+                    }
+                });
+                self.running.insert(key, handle.abort_handle());
+            }
+        }
+    }
+
+    pub fn stop_all(&mut self) {
+        for (_, handle) in self.running.drain() {
+            handle.abort();
+        }
+    }
+}
@@ -4,6 +4,8 @@ use crate::prelude_internal::*;
 pub struct TabController {
     size: usize,
     current: usize,
+    // Areas the controls were last drawn in, for focusing them by clicking
+    areas: Vec<Rect>,
 }
 
 impl TabController {
@@ -11,6 +13,7 @@ impl TabController {
         Self {
             size,
             current: 0,
+            areas: Vec::new(),
         }
     }
 
@@ -29,6 +32,7 @@ impl TabController {
         self.current = idx.min(self.size-1);
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> usize {
         if self.size == 0 {
             return 0;
@@ -60,6 +64,16 @@ impl TabController {
             KeyEvent,
             KeyCode,
         };
+        if let Message::Mouse(_) = msg {
+            return match self.areas.iter().position(|area| msg.clicked_in(*area)) {
+                Some(idx) if idx != self.current => {
+                    self.set_current_index(idx);
+                    log::debug!("in tab_controller::update with {msg:?}; current index: {}", self.current);
+                    Message::Redraw
+                }
+                _ => Message::Noop,
+            };
+        }
         match *msg {
             Message::KeyPress(KeyEvent{code: KeyCode::Tab, ..}) => {
                 self.next();
@@ -77,10 +91,15 @@ impl TabController {
 
     pub fn iter(&self) -> impl std::iter::Iterator<Item=(usize, FocusState)> {
         let current = self.current;
-        (0..self.size).into_iter().map(move |i| (i, if i==current { FocusState::Focus } else { FocusState::Blur }))
+        (0..self.size).map(move |i| (i, if i==current { FocusState::Focus } else { FocusState::Blur }))
     }
     pub fn iter_with_areas(&self, areas: impl IntoIterator<Item=Rect>) -> impl std::iter::Iterator<Item=(usize, Rect, FocusState)> {
         self.iter().zip(areas).map(move |((i, fs), area)| (i, area, fs))
     }
+    // Remember where the controls are drawn so that clicking in one
+    // focuses it
+    pub fn set_areas(&mut self, areas: impl IntoIterator<Item=Rect>) {
+        self.areas = areas.into_iter().collect();
+    }
 }
 
@@ -0,0 +1,218 @@
+use crate::message::Message;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::Notify;
+use tokio::task::AbortHandle;
+use uuid::Uuid;
+
+type BoxedFuture = Pin<Box<dyn Future<Output=Message> + Send>>;
+
+// An arbitrary future to run as a command, resolving to the message
+// to deliver back to the model.
+// Use Message::task(future) to create one. Clones share the future, so
+// only the first one executed will run it.
+#[derive(Clone)]
+pub struct Task {
+    id: Uuid,
+    future: Arc<Mutex<Option<BoxedFuture>>>,
+}
+
+impl Task {
+    pub fn new(future: impl Future<Output=Message> + Send + 'static) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            future: Arc::new(Mutex::new(Some(Box::pin(future)))),
+        }
+    }
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+    pub(crate) async fn execute(self) -> Option<Message> {
+        let future = self.future.lock().unwrap().take();
+        match future {
+            Some(future) => Some(future.await),
+            None => {
+                log::warn!("Task {} was already run", self.id);
+                None
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for Task {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Task({})", self.id)
+    }
+}
+
+// How many commands are still running, with or without an id
+#[derive(Debug, Default)]
+struct Outstanding {
+    count: AtomicUsize,
+    done: Notify,
+}
+
+// Counts a command as outstanding until it is dropped, which happens
+// whether it finishes or is aborted
+struct OutstandingGuard(Arc<Outstanding>);
+
+impl OutstandingGuard {
+    fn new(outstanding: &Arc<Outstanding>) -> Self {
+        outstanding.count.fetch_add(1, Ordering::SeqCst);
+        Self(outstanding.clone())
+    }
+}
+
+impl Drop for OutstandingGuard {
+    fn drop(&mut self) {
+        if self.0.count.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.done.notify_waiters();
+        }
+    }
+}
+
+// The running commands, each under a key of its own so that commands
+// without ids can still be stopped, along with the ids they answer to
+#[derive(Debug, Default)]
+struct Running {
+    handles: HashMap<Uuid, (AbortHandle, Vec<Uuid>)>,
+    keys: HashMap<Uuid, Uuid>,
+}
+
+impl Running {
+    // Forget the command, returning its handle if it was still running
+    fn remove(&mut self, key: Uuid) -> Option<AbortHandle> {
+        let (handle, ids) = self.handles.remove(&key)?;
+        for id in ids {
+            self.keys.remove(&id);
+        }
+        Some(handle)
+    }
+}
+
+// Keeps track of running commands by id so they can be cancelled.
+// Clones share the same set of tasks.
+#[derive(Debug, Clone, Default)]
+pub struct Tasks {
+    running: Arc<Mutex<Running>>,
+    outstanding: Arc<Outstanding>,
+}
+
+impl Tasks {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    // Spawn a future that can be cancelled by any of the ids, and is
+    // stopped by cancel_all whether it has any or not
+    pub fn spawn(&self, ids: Vec<Uuid>, task: impl Future<Output=()> + Send + 'static) {
+        let guard = OutstandingGuard::new(&self.outstanding);
+        let key = Uuid::new_v4();
+        // Hold the lock while spawning so the task can't finish and
+        // forget itself before it has been registered
+        let mut running = self.running.lock().unwrap();
+        let tasks = self.clone();
+        let handle = tokio::spawn(async move {
+            task.await;
+            tasks.running.lock().unwrap().remove(key);
+            drop(guard);
+        });
+        for id in &ids {
+            running.keys.insert(*id, key);
+        }
+        running.handles.insert(key, (handle.abort_handle(), ids));
+    }
+
+    // Abort the task with this id, returning false if it wasn't running.
+    // Any other ids the task had are forgotten along with it.
+    pub fn cancel(&self, id: Uuid) -> bool {
+        let mut running = self.running.lock().unwrap();
+        let Some(key) = running.keys.get(&id).copied() else {
+            return false;
+        };
+        match running.remove(key) {
+            Some(handle) => {
+                handle.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn cancel_all(&self) {
+        let mut running = self.running.lock().unwrap();
+        running.keys.clear();
+        for (_, (handle, _)) in running.handles.drain() {
+            handle.abort();
+        }
+    }
+
+    pub fn is_running(&self, id: Uuid) -> bool {
+        self.running.lock().unwrap().keys.contains_key(&id)
+    }
+
+    // Wait until every command spawned so far has finished or been
+    // aborted, including any spawned while waiting
+    pub async fn idle(&self) {
+        loop {
+            let done = self.outstanding.done.notified();
+            if self.outstanding.count.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+            done.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+    use tokio::time::{Duration, sleep};
+
+    // A task that sets the flag unless it is stopped within a second
+    fn slow(done: &Arc<AtomicBool>) -> impl Future<Output=()> + Send + 'static {
+        let done = done.clone();
+        async move {
+            sleep(Duration::from_secs(1)).await;
+            done.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn cancel_all_stops_tasks_without_ids() {
+        let tasks = Tasks::new();
+        let done = Arc::new(AtomicBool::new(false));
+        tasks.spawn(Vec::new(), slow(&done));
+        tasks.cancel_all();
+        tasks.idle().await;
+        assert!(!done.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn cancelling_one_id_forgets_the_others() {
+        let tasks = Tasks::new();
+        let done = Arc::new(AtomicBool::new(false));
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        tasks.spawn(vec![a, b], slow(&done));
+        assert!(tasks.is_running(b));
+        assert!(tasks.cancel(a));
+        assert!(!tasks.is_running(b));
+        assert!(!tasks.cancel(b));
+        tasks.idle().await;
+        assert!(!done.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn finished_tasks_are_forgotten() {
+        let tasks = Tasks::new();
+        let id = Uuid::new_v4();
+        tasks.spawn(vec![id], async {});
+        tasks.idle().await;
+        assert!(!tasks.is_running(id));
+        assert!(tasks.running.lock().unwrap().handles.is_empty());
+    }
+}
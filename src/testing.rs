@@ -0,0 +1,183 @@
+// Helpers for checking how models render against stored text snapshots.
+//
+// Snapshots live in tests/snapshots/<name>.snap under the crate being
+// tested, unless Snapshots is given another directory. Run the tests with
+// RACCACOONIE_UPDATE_SNAPSHOTS=1 to write the current output instead of
+// comparing against it.
+use crate::prelude_internal::*;
+use ratatui::{
+    Terminal,
+    backend::TestBackend,
+    buffer::Buffer,
+    style::{Color, Style},
+};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+pub const UPDATE_SNAPSHOTS_VAR: &str = "RACCACOONIE_UPDATE_SNAPSHOTS";
+
+// Draw the model's view once into a buffer of the given size
+pub fn render(model: &mut impl Model, width: u16, height: u16) -> Result<Buffer> {
+    let mut term = Terminal::new(TestBackend::new(width, height))?;
+    let mut view_error = None;
+    term.draw(|f: &mut Frame| {
+        let area = f.area();
+        if let Err(err) = model.view(f, area) {
+            view_error = Some(err);
+        }
+    })?;
+    match view_error {
+        Some(err) => Err(err),
+        None => Ok(term.backend().buffer().clone()),
+    }
+}
+
+// The buffer's text, one line per row, without trailing spaces
+pub fn text(buffer: &Buffer) -> String {
+    let mut out = String::new();
+    for y in 0..buffer.area.height {
+        let mut line = String::new();
+        for x in 0..buffer.area.width {
+            line.push_str(buffer[(buffer.area.x + x, buffer.area.y + y)].symbol());
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+// The buffer's text followed by a list of every run of styled cells, as
+// `row:start-end style`, with columns counted from 0 and end exclusive
+pub fn styled_text(buffer: &Buffer) -> String {
+    let mut out = text(buffer);
+    out.push_str("---\n");
+    for y in 0..buffer.area.height {
+        let mut run: Option<(u16, Style)> = None;
+        for x in 0..=buffer.area.width {
+            let style = match x < buffer.area.width {
+                true => buffer[(buffer.area.x + x, buffer.area.y + y)].style(),
+                false => Style::default(),
+            };
+            if let Some((start, run_style)) = run {
+                if run_style == style {
+                    continue;
+                }
+                let _ = writeln!(out, "{y}:{start}-{x} {}", describe(run_style));
+                run = None;
+            }
+            if style != Style::default() {
+                run = Some((x, style));
+            }
+        }
+    }
+    out
+}
+
+fn describe(style: Style) -> String {
+    let mut parts = Vec::new();
+    let color = |color: Option<Color>| color.filter(|c| *c != Color::Reset);
+    if let Some(fg) = color(style.fg) {
+        parts.push(format!("fg={fg}"));
+    }
+    if let Some(bg) = color(style.bg) {
+        parts.push(format!("bg={bg}"));
+    }
+    for (name, _) in style.add_modifier.iter_names() {
+        parts.push(format!("+{}", name.to_lowercase()));
+    }
+    for (name, _) in style.sub_modifier.iter_names() {
+        parts.push(format!("-{}", name.to_lowercase()));
+    }
+    if parts.is_empty() {
+        parts.push("reset".into());
+    }
+    parts.join(" ")
+}
+
+// Where snapshots are stored and whether to write them rather than
+// compare against them. The assert_*_snapshot functions use the defaults:
+// tests/snapshots, updated when RACCACOONIE_UPDATE_SNAPSHOTS is set.
+#[derive(Debug, Clone)]
+pub struct Snapshots {
+    dir: PathBuf,
+    update: bool,
+}
+
+impl Default for Snapshots {
+    fn default() -> Self {
+        let root = std::env::var_os("CARGO_MANIFEST_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_default();
+        Self {
+            dir: root.join("tests").join("snapshots"),
+            update: std::env::var(UPDATE_SNAPSHOTS_VAR).is_ok_and(|value| !value.is_empty() && value != "0"),
+        }
+    }
+}
+
+impl Snapshots {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    pub fn with_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = dir.into();
+        self
+    }
+    pub fn with_update(mut self, update: bool) -> Self {
+        self.update = update;
+        self
+    }
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.snap"))
+    }
+
+    // Compare text against the stored snapshot, panicking with both
+    // versions if they differ, or store it when updating snapshots
+    #[track_caller]
+    pub fn assert(&self, name: &str, actual: &str) {
+        let path = self.path(name);
+        if self.update {
+            std::fs::create_dir_all(&self.dir).expect("creating snapshot directory");
+            std::fs::write(&path, actual).expect("writing snapshot");
+            return;
+        }
+        let expected = match std::fs::read_to_string(&path) {
+            Ok(expected) => expected,
+            Err(err) => panic!(
+                "No snapshot {name} at {}: {err}\nRun with {UPDATE_SNAPSHOTS_VAR}=1 to create it. Got:\n{actual}",
+                path.display(),
+            ),
+        };
+        if expected != actual {
+            panic!(
+                "Snapshot {name} does not match {}\nRun with {UPDATE_SNAPSHOTS_VAR}=1 to update it.\n\
+                Expected:\n{expected}\nGot:\n{actual}",
+                path.display(),
+            );
+        }
+    }
+}
+
+#[track_caller]
+pub fn assert_snapshot(name: &str, actual: &str) {
+    Snapshots::new().assert(name, actual);
+}
+
+#[track_caller]
+pub fn assert_buffer_snapshot(name: &str, buffer: &Buffer) {
+    assert_snapshot(name, &text(buffer));
+}
+
+#[track_caller]
+pub fn assert_styled_snapshot(name: &str, buffer: &Buffer) {
+    assert_snapshot(name, &styled_text(buffer));
+}
+
+// Render the model at the given size and compare its text to the snapshot
+#[track_caller]
+pub fn assert_model_snapshot(name: &str, model: &mut impl Model, width: u16, height: u16) {
+    match render(model, width, height) {
+        Ok(buffer) => assert_buffer_snapshot(name, &buffer),
+        Err(err) => panic!("Error rendering {name}: {err}"),
+    }
+}
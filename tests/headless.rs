@@ -0,0 +1,276 @@
+use raccacoonie::prelude::*;
+use raccacoonie::testing::text;
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use std::time::Duration;
+
+fn key(code: KeyCode) -> Message {
+    Message::KeyPress(KeyEvent::from(code))
+}
+
+fn type_text(text: &str) -> impl Iterator<Item=Message> + '_ {
+    text.chars().map(|c| key(KeyCode::Char(c)))
+}
+
+// A name field over a list of fruit, quitting once a fruit is chosen
+struct Picker {
+    name: InputControl,
+    list: ListView<String>,
+    in_list: bool,
+    choice: Option<usize>,
+}
+
+impl Picker {
+    fn new() -> Self {
+        let mut name = InputControl::from_label("Name");
+        name.set_focus(FocusState::Focus);
+        Self {
+            name,
+            list: ListView::new("Fruit", ["apple", "banana", "cherry"].map(String::from)),
+            in_list: false,
+            choice: None,
+        }
+    }
+}
+
+impl Model for Picker {
+    fn view(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let [name, list] = Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(area);
+        self.name.view(frame, name)?;
+        self.list.view(frame, list)
+    }
+    fn init(&mut self) -> Message {
+        self.list.init()
+    }
+    fn update(&mut self, msg: Message) -> Message {
+        match msg {
+            Message::Choice(i) => {
+                self.choice = Some(i);
+                Message::Quit
+            }
+            // Tab moves on from the name to the list
+            Message::KeyPress(KeyEvent { code: KeyCode::Tab, .. }) => {
+                self.name.set_focus(FocusState::Blur);
+                self.list.set_focus(FocusState::Focus);
+                self.in_list = true;
+                Message::Redraw
+            }
+            msg if self.in_list => self.list.update(msg),
+            msg => self.name.update(msg),
+        }
+    }
+}
+
+impl Runner for Picker {}
+
+impl RunnerWithOutput for Picker {
+    type Output = Option<usize>;
+    fn output(&mut self) -> Self::Output {
+        self.choice
+    }
+}
+
+#[tokio::test]
+async fn drives_input_and_list() {
+    let script = type_text("Ann")
+        .chain([key(KeyCode::Tab), key(KeyCode::Down), key(KeyCode::Down), key(KeyCode::Enter)])
+        .collect::<Vec<_>>();
+    let (picker, frames) = Picker::new().run_headless(TestBackend::new(20, 8), script).await.unwrap();
+
+    // One frame after init and one for each message but the last, which quit
+    assert_eq!(frames.len(), 7);
+    assert_eq!(picker.name.value(), "Ann");
+    assert_eq!(picker.choice, Some(2));
+    assert_eq!(picker.list.chosen.as_deref(), Some("cherry"));
+    assert_eq!(text(&frames[3]), "\
+╔Name══════════════╗
+║Ann               ║
+╚══════════════════╝
+┌Fruit─────────────┐
+│➡︎ apple           │
+│  banana          │
+│  cherry          │
+└──────────────────┘
+");
+    assert!(text(&frames[6]).contains("➡︎ cherry"));
+}
+
+#[tokio::test]
+async fn filters_list_while_searching() {
+    let script = [key(KeyCode::Char('/'))].into_iter()
+        .chain(type_text("an"))
+        .collect::<Vec<_>>();
+    let list = ListView::new("Fruit", ["apple", "banana", "cherry"].map(String::from));
+    let (_, frames) = Searcher(list).run_headless(TestBackend::new(20, 8), script).await.unwrap();
+    let last = text(frames.last().unwrap());
+    assert!(last.contains("banana"));
+    assert!(!last.contains("apple"));
+    assert!(!last.contains("cherry"));
+}
+
+struct Searcher(ListView<String>);
+
+impl Model for Searcher {
+    fn view(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.0.view(frame, area)
+    }
+    fn init(&mut self) -> Message {
+        self.0.set_focus(FocusState::Focus);
+        self.0.init()
+    }
+    fn update(&mut self, msg: Message) -> Message {
+        self.0.update(msg)
+    }
+}
+
+impl Runner for Searcher {}
+
+// Counts keys pressed, but only once a slow task says it has seen each one
+#[derive(Default)]
+struct Slow {
+    seen: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Seen;
+
+impl Model for Slow {
+    fn view(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        frame.render_widget(format!("seen {}", self.seen), area);
+        Ok(())
+    }
+    fn update(&mut self, msg: Message) -> Message {
+        if msg.is::<Seen>() {
+            self.seen += 1;
+            return Message::Redraw;
+        }
+        match msg {
+            Message::KeyPress(_) => Message::task(async {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                Message::custom(Seen)
+            }).1,
+            _ => Message::Noop,
+        }
+    }
+}
+
+impl Runner for Slow {}
+
+#[tokio::test]
+async fn waits_for_commands_before_each_frame() {
+    let (slow, frames) = Slow::default()
+        .run_headless(TestBackend::new(10, 1), type_text("ab"))
+        .await
+        .unwrap();
+    assert_eq!(slow.seen, 2);
+    let frames = frames.iter().map(text).collect::<Vec<_>>();
+    assert_eq!(frames, ["seen 0\n", "seen 1\n", "seen 2\n"]);
+}
+
+// Counts keys pressed, once its data has loaded
+#[derive(Default)]
+struct Loading {
+    loaded: bool,
+    keys: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Loaded;
+
+impl Model for Loading {
+    fn view(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        frame.render_widget(format!("keys {}", self.keys), area);
+        Ok(())
+    }
+    fn init_async(&mut self) -> Option<Task> {
+        Some(Task::new(async {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            Message::custom(Loaded)
+        }))
+    }
+    fn update(&mut self, msg: Message) -> Message {
+        if msg.is::<Loaded>() {
+            self.loaded = true;
+        } else if let Message::KeyPress(_) = msg {
+            assert!(self.loaded, "got a key before loading");
+            self.keys += 1;
+        }
+        Message::Redraw
+    }
+}
+
+impl Runner for Loading {}
+
+#[tokio::test]
+async fn loads_before_the_script_starts() {
+    let (loading, frames) = Loading::default()
+        .run_headless(TestBackend::new(10, 1), type_text("ab"))
+        .await
+        .unwrap();
+    assert!(loading.loaded);
+    assert_eq!(loading.keys, 2);
+    let frames = frames.iter().map(text).collect::<Vec<_>>();
+    assert_eq!(frames, ["keys 0\n", "keys 1\n", "keys 2\n"]);
+}
+
+// Panics in update on '!', or in a task on '?'
+struct Panicky;
+
+impl Model for Panicky {
+    fn view(&mut self, _frame: &mut Frame, _area: Rect) -> Result<()> {
+        Ok(())
+    }
+    fn update(&mut self, msg: Message) -> Message {
+        match msg {
+            Message::KeyPress(KeyEvent { code: KeyCode::Char('!'), .. }) => panic!("update blew up"),
+            Message::KeyPress(KeyEvent { code: KeyCode::Char('?'), .. }) => Message::task(async {
+                panic!("task blew up")
+            }).1,
+            _ => Message::Noop,
+        }
+    }
+}
+
+impl Runner for Panicky {}
+
+#[tokio::test]
+async fn panic_in_update_is_an_error() {
+    let result = Panicky.run_headless(TestBackend::new(10, 1), type_text("a!b")).await;
+    let Err(Error::Panic { message, .. }) = result else {
+        panic!("expected a panic error");
+    };
+    assert_eq!(message, "update blew up");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn panic_in_task_is_an_error() {
+    let result = Panicky.run_headless(TestBackend::new(10, 1), type_text("a?b")).await;
+    let Err(Error::Panic { message, .. }) = result else {
+        panic!("expected a panic error");
+    };
+    assert_eq!(message, "task blew up");
+}
+
+// Quits as soon as it has loaded, before any input arrives
+struct GivesUp;
+
+impl Model for GivesUp {
+    fn view(&mut self, _frame: &mut Frame, _area: Rect) -> Result<()> {
+        Ok(())
+    }
+    fn init_async(&mut self) -> Option<Task> {
+        Some(Task::new(async { Message::Quit }))
+    }
+    fn update(&mut self, msg: Message) -> Message {
+        assert!(!matches!(msg, Message::Quit | Message::KeyPress(_)), "got {msg:?}");
+        Message::Noop
+    }
+}
+
+impl Runner for GivesUp {}
+
+#[tokio::test]
+async fn runner_handles_what_loading_resolves_to() {
+    let (_, frames) = GivesUp.run_headless(TestBackend::new(10, 1), type_text("ab")).await.unwrap();
+    assert!(frames.is_empty());
+}
@@ -0,0 +1,147 @@
+use raccacoonie::prelude::*;
+use raccacoonie::testing::text;
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use std::sync::{Arc, Mutex};
+
+fn key(code: KeyCode) -> Message {
+    Message::KeyPress(KeyEvent::from(code))
+}
+
+fn downs(count: usize) -> Vec<Message> {
+    vec![key(KeyCode::Down); count]
+}
+
+// Shows a list from any source, keeping the last error it reported. While
+// hold is set, commands from the list are held back until 'r' is pressed.
+struct Shown<S: ListSource<String>> {
+    list: ListView<String, S>,
+    error: Option<Error>,
+    hold: bool,
+    held: Message,
+}
+
+impl<S: ListSource<String>> Shown<S> {
+    fn new(source: S) -> Self {
+        Self {
+            list: ListView::from_source("Items", source),
+            error: None,
+            hold: false,
+            held: Message::Noop,
+        }
+    }
+}
+
+impl<S: ListSource<String>> Model for Shown<S> {
+    fn view(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.list.view(frame, area)
+    }
+    fn init(&mut self) -> Message {
+        self.list.set_focus(FocusState::Focus);
+        self.list.init()
+    }
+    fn update(&mut self, msg: Message) -> Message {
+        match msg {
+            Message::Error(e) => {
+                self.error = Some(e);
+                Message::Noop
+            }
+            Message::KeyPress(KeyEvent { code: KeyCode::Char('r'), .. }) => {
+                std::mem::take(&mut self.held)
+            }
+            msg => match self.list.update(msg) {
+                next if self.hold && next.is_command() => {
+                    self.held = std::mem::take(&mut self.held).and(next);
+                    Message::Redraw
+                }
+                next => next,
+            },
+        }
+    }
+}
+
+impl<S: ListSource<String>> Runner for Shown<S> {}
+
+// Pages of up to 23 numbered items, noting the offset of each page asked for
+fn numbers(offsets: &Arc<Mutex<Vec<usize>>>) -> PagedSource<String> {
+    let offsets = offsets.clone();
+    PagedSource::new(10, move |offset, limit| {
+        offsets.lock().unwrap().push(offset);
+        async move { Ok((offset..(offset + limit).min(23)).map(|i| format!("item {i}")).collect()) }
+    })
+}
+
+#[tokio::test]
+async fn loads_next_page_near_the_end() {
+    let offsets = Arc::new(Mutex::new(Vec::new()));
+    let (shown, _) = Shown::new(numbers(&offsets)).run_headless(TestBackend::new(20, 8), downs(4)).await.unwrap();
+    assert_eq!(*offsets.lock().unwrap(), [0]);
+    assert_eq!(shown.list.source().len(), 10);
+
+    let offsets = Arc::new(Mutex::new(Vec::new()));
+    let (shown, _) = Shown::new(numbers(&offsets)).run_headless(TestBackend::new(20, 8), downs(5)).await.unwrap();
+    assert_eq!(*offsets.lock().unwrap(), [0, 10]);
+    assert_eq!(shown.list.source().len(), 20);
+    assert_eq!(shown.list.selected().map(String::as_str), Some("item 5"));
+}
+
+#[tokio::test]
+async fn shows_a_row_while_loading() {
+    let offsets = Arc::new(Mutex::new(Vec::new()));
+    let mut shown = Shown::new(numbers(&offsets));
+    shown.hold = true;
+    let mut script = downs(5);
+    script.push(key(KeyCode::Char('r')));
+    let (shown, frames) = shown.run_headless(TestBackend::new(20, 14), script).await.unwrap();
+
+    let loading = text(&frames[5]);
+    assert!(loading.contains("item 9"), "{loading}");
+    assert!(loading.contains("loading more…"), "{loading}");
+    let loaded = text(&frames[6]);
+    assert!(loaded.contains("item 10"), "{loaded}");
+    assert!(!loaded.contains("loading more…"), "{loaded}");
+    assert_eq!(shown.list.source().len(), 20);
+}
+
+#[tokio::test]
+async fn stops_after_a_short_page() {
+    let offsets = Arc::new(Mutex::new(Vec::new()));
+    let (shown, frames) = Shown::new(numbers(&offsets)).run_headless(TestBackend::new(20, 8), downs(22)).await.unwrap();
+    assert_eq!(*offsets.lock().unwrap(), [0, 10, 20]);
+    assert_eq!(shown.list.source().len(), 23);
+    assert!(!shown.list.source().has_more());
+    let last = text(frames.last().unwrap());
+    assert!(last.contains("➡︎ item 22"), "{last}");
+}
+
+#[tokio::test]
+async fn an_empty_page_is_the_last() {
+    let offsets = Arc::new(Mutex::new(Vec::new()));
+    let noted = offsets.clone();
+    let source = PagedSource::new(0, move |offset, _| {
+        noted.lock().unwrap().push(offset);
+        async { Ok(Vec::<String>::new()) }
+    });
+    let (shown, _) = Shown::new(source).run_headless(TestBackend::new(20, 8), downs(3)).await.unwrap();
+    assert_eq!(*offsets.lock().unwrap(), [0]);
+    assert!(!shown.list.source().has_more());
+}
+
+#[tokio::test]
+async fn streams_lines_and_reports_exit_status() {
+    let command = ShellCommand::new("sh", ["-c", "printf 'one\\ntwo\\nthree\\n'; exit 3"]);
+    let (shown, frames) = Shown::new(StreamedSource::lines(command))
+        .run_headless(TestBackend::new(20, 8), downs(2))
+        .await
+        .unwrap();
+    let items = (0..shown.list.source().len())
+        .filter_map(|i| shown.list.get(i).cloned())
+        .collect::<Vec<_>>();
+    assert_eq!(items, ["one", "two", "three"]);
+    assert_eq!(shown.list.selected().map(String::as_str), Some("three"));
+    assert!(!text(frames.last().unwrap()).contains("loading more…"));
+    let Some(Error::OwnedError(error)) = shown.error else {
+        panic!("expected an error, got {:?}", shown.error);
+    };
+    assert_eq!(error, "command exited with status 3");
+}
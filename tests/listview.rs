@@ -0,0 +1,174 @@
+use raccacoonie::prelude::*;
+use raccacoonie::testing::{render, text};
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::widgets::ListItem;
+
+fn key(code: KeyCode) -> Message {
+    Message::KeyPress(KeyEvent::from(code))
+}
+
+fn press(list: &mut ListView<String>, keys: &str) -> Message {
+    keys.chars().map(|c| list.update(key(KeyCode::Char(c)))).last().unwrap_or_default()
+}
+
+fn fruit() -> ListView<String> {
+    let mut list = ListView::new("Fruit", ["apple", "banana", "cherry", "date"].map(String::from));
+    list.init();
+    list
+}
+
+#[test]
+fn choice_is_indexed_in_full_list() {
+    let mut list = fruit();
+    press(&mut list, "/rr");
+    list.update(key(KeyCode::Enter));
+    let Message::Choice(i) = list.update(key(KeyCode::Enter)) else {
+        panic!("expected a choice");
+    };
+    assert_eq!(i, 2);
+    assert_eq!(list.get(i).map(String::as_str), Some("cherry"));
+    assert_eq!(list.filtered_get(0).map(String::as_str), Some("cherry"));
+    assert_eq!(list.chosen.as_deref(), Some("cherry"));
+}
+
+#[test]
+fn choices_are_indexed_in_full_list() {
+    let mut list = fruit().with_multi_select(true);
+    press(&mut list, "/a");
+    list.update(key(KeyCode::Enter));
+    list.update(key(KeyCode::Down));
+    press(&mut list, " ");
+    let Message::Choices(indexes) = list.update(key(KeyCode::Enter)) else {
+        panic!("expected choices");
+    };
+    assert_eq!(indexes, [1]);
+    assert_eq!(list.get(indexes[0]).map(String::as_str), Some("banana"));
+    assert_eq!(list.chosen_items, ["banana"]);
+}
+
+// A source whose items are swapped out by a Replace message, or added to
+// by an Append one
+#[derive(Debug, Default)]
+struct Swappable(Vec<String>);
+
+#[derive(Debug, Clone)]
+struct Replace(Vec<&'static str>);
+
+#[derive(Debug, Clone)]
+struct Append(Vec<&'static str>);
+
+impl ListSource<String> for Swappable {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+    fn get(&self, index: usize) -> Option<&String> {
+        self.0.get(index)
+    }
+    fn update(&mut self, msg: &Message) -> Option<Message> {
+        if let Some(Replace(items)) = msg.downcast_ref() {
+            self.0 = items.iter().map(|s| s.to_string()).collect();
+        } else if let Some(Append(items)) = msg.downcast_ref() {
+            self.0.extend(items.iter().map(|s| s.to_string()));
+        } else {
+            return None;
+        }
+        Some(Message::Noop)
+    }
+}
+
+fn swappable(items: &[&'static str]) -> ListView<String, Swappable> {
+    let mut list = ListView::from_source("Items", Swappable::default());
+    list.init();
+    list.update(Message::custom(Append(items.to_vec())));
+    list
+}
+
+#[test]
+fn shrinking_source_resets_selection() {
+    let mut list = swappable(&["one", "two", "three"]);
+    list.update(key(KeyCode::Char('/')));
+    list.update(key(KeyCode::Char('o')));
+    list.update(key(KeyCode::Enter));
+    list.update(key(KeyCode::Down));
+    list.update(key(KeyCode::Down));
+    assert_eq!(list.selected().map(String::as_str), Some("one"));
+
+    // Nothing left matches the search
+    list.update(Message::custom(Replace(vec!["six"])));
+    assert_eq!(list.selected(), None);
+    list.update(key(KeyCode::Down));
+    list.update(key(KeyCode::Up));
+    assert_eq!(list.selected(), None);
+
+    list.update(Message::custom(Append(vec!["seven", "ten"])));
+    assert_eq!(list.selected(), None);
+    list.update(Message::custom(Append(vec!["zero"])));
+    assert_eq!(list.selected().map(String::as_str), Some("zero"));
+}
+
+#[test]
+fn fuzzy_appends_keep_the_selected_item() {
+    let mut list = swappable(&["a-b-c", "ab-c", "x"]).with_match_mode(MatchMode::Fuzzy);
+    for c in "/abc".chars() {
+        list.update(key(KeyCode::Char(c)));
+    }
+    list.update(key(KeyCode::Enter));
+    assert_eq!(list.selected().map(String::as_str), Some("ab-c"));
+    list.update(key(KeyCode::Down));
+    assert_eq!(list.selected().map(String::as_str), Some("a-b-c"));
+
+    // A better match arrives and is ranked first
+    list.update(Message::custom(Append(vec!["abc"])));
+    assert_eq!(list.selected().map(String::as_str), Some("a-b-c"));
+    list.update(key(KeyCode::Down));
+    assert_eq!(list.selected().map(String::as_str), Some("abc"));
+}
+
+#[derive(Debug, Clone)]
+struct Host {
+    name: &'static str,
+    region: &'static str,
+}
+
+impl std::fmt::Display for Host {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+fn hosts() -> ListView<Host> {
+    let hosts = [Host { name: "web-1", region: "us-east" }, Host { name: "db-1", region: "eu-west" }];
+    let mut list = ListView::new("Hosts", hosts).with_search_key(|host| format!("{} {}", host.name, host.region));
+    list.init();
+    list
+}
+
+#[test]
+fn search_key_does_not_change_what_is_shown() {
+    let mut list = hosts();
+    for c in "/eu".chars() {
+        list.update(key(KeyCode::Char(c)));
+    }
+    let shown = text(&render(&mut list, 20, 6).unwrap());
+    assert!(shown.contains("db-1 "), "{shown}");
+    assert!(!shown.contains("eu-west"), "{shown}");
+    assert!(!shown.contains("web-1"), "{shown}");
+}
+
+#[test]
+fn renderer_gets_key_and_width() {
+    let mut list = hosts().with_renderer(|host, state| {
+        let name = state.highlight(state.key.split(' ').next().unwrap_or_default());
+        let pad = (state.width as usize).saturating_sub(host.name.len() + host.region.len());
+        let mut line = name;
+        line.push_span(" ".repeat(pad));
+        line.push_span(host.region);
+        ListItem::new(line)
+    });
+    assert_eq!(text(&render(&mut list, 24, 4).unwrap()), "\
+┌Hosts─────────────────┐
+│➡︎ web-1        us-east│
+│  db-1         eu-west│
+└──────────────────────┘
+");
+}
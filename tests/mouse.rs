@@ -0,0 +1,91 @@
+use raccacoonie::prelude::*;
+use raccacoonie::testing::{render, text};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::text::Line;
+use ratatui::widgets::ListItem;
+
+fn click(column: u16, row: u16) -> Message {
+    Message::Mouse(MouseEvent {
+        kind: MouseEventKind::Down(MouseButton::Left),
+        column,
+        row,
+        modifiers: KeyModifiers::NONE,
+    })
+}
+
+fn down(list: &mut ListView<String>, count: usize) {
+    for _ in 0..count {
+        list.update(Message::KeyPress(KeyEvent::from(KeyCode::Down)));
+    }
+}
+
+fn numbers() -> ListView<String> {
+    let mut list = ListView::new("Numbers", (0..20).map(|i| format!("item {i}")));
+    list.set_focus(FocusState::Focus);
+    list.init();
+    list
+}
+
+#[test]
+fn click_selects_row_in_scrolled_list() {
+    let mut list = numbers();
+    down(&mut list, 10);
+    let shown = text(&render(&mut list, 20, 6).unwrap());
+    assert!(shown.starts_with("╔Numbers═══════════╗\n║  item 7 "), "{shown}");
+
+    // The second row of the list, below the border
+    assert!(matches!(list.update(click(5, 2)), Message::Redraw));
+    assert_eq!(list.selected().map(String::as_str), Some("item 8"));
+}
+
+#[test]
+fn click_counts_lines_of_tall_items() {
+    let mut list = numbers().with_renderer(|item, state| {
+        let lines = if item == "item 9" { vec!["item 9", "  more"] } else { vec![item.as_str()] };
+        ListItem::new(lines.into_iter().map(|line| state.highlight(line)).collect::<Vec<Line>>())
+    });
+    down(&mut list, 12);
+    assert_eq!(text(&render(&mut list, 20, 8).unwrap()), "\
+╔Numbers═══════════╗
+║  item 8          ║
+║  item 9          ║
+║    more          ║
+║  item 10         ║
+║  item 11         ║
+║➡︎ item 12         ║
+╚══════════════════╝
+");
+
+    // Both lines of the tall item select it, and the one after is next
+    list.update(click(5, 2));
+    assert_eq!(list.selected().map(String::as_str), Some("item 9"));
+    list.update(click(5, 3));
+    assert_eq!(list.selected().map(String::as_str), Some("item 9"));
+    list.update(click(5, 4));
+    assert_eq!(list.selected().map(String::as_str), Some("item 10"));
+}
+
+#[test]
+fn click_focuses_tab() {
+    let mut tabs = TabController::new(3);
+    tabs.set_areas([Rect::new(0, 0, 10, 3), Rect::new(10, 0, 10, 3), Rect::new(20, 0, 10, 3)]);
+    assert!(matches!(tabs.update(&click(25, 1)), Message::Redraw));
+    assert_eq!(tabs.get_current_index(), 2);
+    // Clicking the current tab or outside all of them changes nothing
+    assert!(matches!(tabs.update(&click(25, 1)), Message::Noop));
+    assert!(matches!(tabs.update(&click(35, 1)), Message::Noop));
+    assert_eq!(tabs.get_current_index(), 2);
+}
+
+#[test]
+fn click_focuses_and_presses_button() {
+    let mut buttons = ButtonBar::ok_cancel();
+    render(&mut buttons, 24, 3).unwrap();
+    let Message::Batch(msgs) = buttons.update(click(18, 1)) else {
+        panic!("expected a redraw and a press");
+    };
+    assert!(matches!(msgs[..], [Message::Redraw, Message::No]));
+    // Now focused, so Enter presses it too
+    assert!(matches!(buttons.update(Message::KeyPress(KeyEvent::from(KeyCode::Enter))), Message::No));
+    assert!(matches!(buttons.update(click(30, 1)), Message::Noop));
+}
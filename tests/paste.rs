@@ -0,0 +1,46 @@
+use raccacoonie::prelude::*;
+use raccacoonie::testing::{render, text};
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+
+fn key(code: KeyCode) -> Message {
+    Message::KeyPress(KeyEvent::from(code))
+}
+
+fn paste(text: &str) -> Message {
+    Message::Paste(text.to_string())
+}
+
+#[test]
+fn paste_goes_in_at_the_cursor() {
+    let mut input = InputControl::from_value("ad");
+    input.update(key(KeyCode::Left));
+    assert!(matches!(input.update(paste("bc\n")), Message::Redraw));
+    assert_eq!(input.value(), "abcd");
+    // The cursor ends up after the pasted text
+    input.update(key(KeyCode::Char('!')));
+    assert_eq!(input.value(), "abc!d");
+}
+
+#[test]
+fn pasted_lines_are_joined() {
+    let mut input = InputControl::default();
+    input.update(paste("one\ntwo\r\nthree\r\n"));
+    assert_eq!(input.value(), "one two three");
+}
+
+#[test]
+fn list_takes_paste_only_while_searching() {
+    let mut list = ListView::new("Fruit", ["apple", "banana", "cherry"].map(String::from));
+    list.set_focus(FocusState::Focus);
+    list.init();
+    assert!(matches!(list.update(paste("an")), Message::Noop));
+    assert!(text(&render(&mut list, 20, 8).unwrap()).contains("cherry"));
+
+    list.update(key(KeyCode::Char('/')));
+    list.update(paste("an\n"));
+    let shown = text(&render(&mut list, 20, 8).unwrap());
+    assert!(shown.contains("║an                ║"), "{shown}");
+    assert!(shown.contains("banana"), "{shown}");
+    assert!(!shown.contains("apple"), "{shown}");
+    assert!(!shown.contains("cherry"), "{shown}");
+}
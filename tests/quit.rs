@@ -0,0 +1,60 @@
+use raccacoonie::prelude::*;
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+// Counts keys, and only lets itself be quit once it has seen two
+#[derive(Default)]
+struct Stubborn {
+    keys: Vec<KeyEvent>,
+    asked: usize,
+}
+
+impl Model for Stubborn {
+    fn view(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        frame.render_widget(format!("keys {}", self.keys.len()), area);
+        Ok(())
+    }
+    fn update(&mut self, msg: Message) -> Message {
+        if let Message::KeyPress(key) = msg {
+            self.keys.push(key);
+        }
+        Message::Redraw
+    }
+    fn confirm_quit(&mut self) -> bool {
+        self.asked += 1;
+        self.keys.len() >= 2
+    }
+}
+
+impl Runner for Stubborn {}
+
+fn ctrl_c() -> KeyEvent {
+    KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)
+}
+
+// What the runner makes of a key press with these global keys
+fn read(keys: &GlobalKeys, key: KeyEvent) -> Message {
+    keys.message_for(&key).unwrap_or(Message::KeyPress(key))
+}
+
+#[tokio::test]
+async fn vetoed_quit_keeps_running() {
+    let keys = RunOptions::new().keys;
+    let a = KeyEvent::from(KeyCode::Char('a'));
+    let script = [read(&keys, ctrl_c()), read(&keys, a), read(&keys, a), read(&keys, ctrl_c()), read(&keys, a)];
+    let (stubborn, frames) = Stubborn::default().run_headless(TestBackend::new(10, 1), script).await.unwrap();
+    assert_eq!(stubborn.asked, 2);
+    assert_eq!(stubborn.keys, [a, a]);
+    // Init, then every message up to the quit that was allowed
+    assert_eq!(frames.len(), 4);
+}
+
+#[tokio::test]
+async fn unbound_quit_key_reaches_the_model() {
+    let keys = RunOptions::new().with_quit_key(None).keys;
+    let script = [read(&keys, ctrl_c()), read(&keys, ctrl_c())];
+    let (stubborn, frames) = Stubborn::default().run_headless(TestBackend::new(10, 1), script).await.unwrap();
+    assert_eq!(stubborn.asked, 0);
+    assert_eq!(stubborn.keys, [ctrl_c(), ctrl_c()]);
+    assert_eq!(frames.len(), 3);
+}
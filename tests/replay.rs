@@ -0,0 +1,59 @@
+use raccacoonie::prelude::*;
+use raccacoonie::recording::{RecordedEntry, RecordedEvent};
+use raccacoonie::testing::text;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use std::io::Write;
+
+// Shows the keys typed and the size it was drawn at
+#[derive(Default)]
+struct Typist(String);
+
+impl Model for Typist {
+    fn view(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        frame.render_widget(format!("{}x{} {}", area.width, area.height, self.0), area);
+        Ok(())
+    }
+    fn update(&mut self, msg: Message) -> Message {
+        if let Message::KeyPress(KeyEvent { code: KeyCode::Char(c), .. }) = msg {
+            self.0.push(c);
+        }
+        Message::Redraw
+    }
+}
+
+impl Runner for Typist {}
+
+fn entry(at_ms: u64, (width, height): (u16, u16), event: RecordedEvent) -> RecordedEntry {
+    RecordedEntry { at_ms, width, height, event }
+}
+
+fn key(c: char) -> RecordedEvent {
+    RecordedEvent::Key(KeyEvent::from(KeyCode::Char(c)))
+}
+
+#[tokio::test]
+async fn replays_a_recording() {
+    // The terminal grew between the two keys without a resize being
+    // recorded, then quit
+    let entries = [
+        entry(10, (10, 1), key('a')),
+        entry(20, (12, 2), key('b')),
+        entry(30, (12, 2), RecordedEvent::Quit),
+        entry(40, (12, 2), key('c')),
+    ];
+    let path = std::env::temp_dir().join(format!("raccacoonie-replay-{}.jsonl", std::process::id()));
+    let mut file = std::fs::File::create(&path).unwrap();
+    for entry in &entries {
+        writeln!(file, "{}", serde_json::to_string(entry).unwrap()).unwrap();
+    }
+    drop(file);
+
+    let result = Typist::default().replay(&path).await;
+    std::fs::remove_file(&path).unwrap();
+    let (typist, frames) = result.unwrap();
+    assert_eq!(typist.0, "ab");
+    let sizes = frames.iter().map(|frame| (frame.area.width, frame.area.height)).collect::<Vec<_>>();
+    assert_eq!(sizes, [(10, 1), (10, 1), (12, 2), (12, 2)]);
+    let frames = frames.iter().map(text).collect::<Vec<_>>();
+    assert_eq!(frames, ["10x1\n", "10x1 a\n", "12x2 a\n\n", "12x2 ab\n\n"]);
+}
@@ -0,0 +1,66 @@
+use raccacoonie::prelude::*;
+use raccacoonie::tasks::Tasks;
+use ratatui::backend::TestBackend;
+
+// Collects the lines and exit status of one shell command
+struct Collect {
+    command: Option<ShellCommand>,
+    lines: Vec<(OutputStream, String)>,
+    status: Option<Option<i32>>,
+}
+
+impl Collect {
+    fn new(command: ShellCommand) -> Self {
+        Self { command: Some(command), lines: Vec::new(), status: None }
+    }
+}
+
+impl Model for Collect {
+    fn view(&mut self, _frame: &mut Frame, _area: Rect) -> Result<()> {
+        Ok(())
+    }
+    fn init(&mut self) -> Message {
+        match self.command.take() {
+            Some(command) => Message::shell(command).1,
+            None => Message::Noop,
+        }
+    }
+    fn update(&mut self, msg: Message) -> Message {
+        match msg {
+            Message::ShellCommandLine { stream, line, .. } => self.lines.push((stream, line)),
+            Message::ShellCommandExited { status, .. } => self.status = Some(status),
+            _ => (),
+        }
+        Message::Noop
+    }
+}
+
+impl Runner for Collect {}
+
+#[tokio::test]
+async fn keeps_reading_past_invalid_utf8() {
+    let script = String::from(r"printf 'one\n\377two\r\nthree'; printf 'err\n' >&2");
+    let command = ShellCommand::new("sh", vec!["-c".to_string(), script]);
+    let (collect, _) = Collect::new(command).run_headless(TestBackend::new(10, 1), []).await.unwrap();
+    let stdout = collect.lines.iter()
+        .filter(|(stream, _)| *stream == OutputStream::Stdout)
+        .map(|(_, line)| line.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(stdout, ["one", "\u{fffd}two", "three"]);
+    assert!(collect.lines.contains(&(OutputStream::Stderr, "err".to_string())));
+    assert_eq!(collect.status, Some(Some(0)));
+}
+
+#[tokio::test]
+async fn commands_without_ids_stop_with_their_tasks() {
+    let marker = std::env::temp_dir().join(format!("raccacoonie-{}", std::process::id()));
+    let _ = std::fs::remove_file(&marker);
+    let script = format!("sleep 0.3; touch '{}'", marker.display());
+    let tasks = Tasks::default();
+    let (tx, _rx) = tokio::sync::mpsc::channel(1);
+    Message::ShellCommand(vec!["sh".into(), "-c".into(), script]).spawn(tx, &tasks);
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    tasks.cancel_all();
+    tokio::time::sleep(std::time::Duration::from_millis(600)).await;
+    assert!(!marker.exists(), "the command kept running");
+}
@@ -0,0 +1,78 @@
+use raccacoonie::prelude::*;
+use raccacoonie::testing::{Snapshots, assert_model_snapshot, assert_styled_snapshot, render};
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use std::panic::catch_unwind;
+
+fn key(code: KeyCode) -> Message {
+    Message::KeyPress(KeyEvent::from(code))
+}
+
+fn regions() -> ListView<String> {
+    let items = ["US East", "US West", "EU Central", "Asia Pacific"].map(String::from);
+    let mut list = ListView::new("Regions", items);
+    list.set_focus(FocusState::Focus);
+    list.init();
+    list
+}
+
+#[test]
+fn listview() {
+    let mut list = regions();
+    list.update(key(KeyCode::Down));
+    assert_styled_snapshot("listview", &render(&mut list, 24, 7).unwrap());
+}
+
+#[test]
+fn listview_search() {
+    let mut list = regions().with_match_mode(MatchMode::Fuzzy);
+    for c in "/usw".chars() {
+        list.update(key(KeyCode::Char(c)));
+    }
+    assert_styled_snapshot("listview_search", &render(&mut list, 24, 8).unwrap());
+}
+
+#[test]
+fn listview_multi_select() {
+    let mut list = regions().with_multi_select(true);
+    list.update(key(KeyCode::Char(' ')));
+    list.update(key(KeyCode::Down));
+    assert_model_snapshot("listview_multi_select", &mut list, 24, 6);
+}
+
+#[test]
+fn button_bar() {
+    let mut buttons = ButtonBar::ok_cancel();
+    buttons.set_focus(1);
+    assert_styled_snapshot("button_bar", &render(&mut buttons, 24, 3).unwrap());
+}
+
+#[test]
+fn popup() {
+    assert_styled_snapshot("popup", &render(&mut Popup::new("Saved 3 files"), 30, 12).unwrap());
+}
+
+#[test]
+fn log_viewer() {
+    let mut viewer = LogViewer::new(regions());
+    assert_model_snapshot("log_viewer", &mut viewer, 30, 10);
+    viewer.update(key(KeyCode::Char('?')));
+    assert_model_snapshot("log_viewer_shown", &mut viewer, 30, 10);
+}
+
+#[test]
+fn updates_then_compares() {
+    let dir = std::env::temp_dir().join(format!("raccacoonie-snapshots-{}", std::process::id()));
+    let snapshots = Snapshots::new().with_dir(&dir).with_update(false);
+    let path = snapshots.path("check");
+    assert_eq!(path, dir.join("check.snap"));
+
+    // Missing snapshots fail until written
+    assert!(catch_unwind(|| snapshots.assert("check", "first\n")).is_err());
+    snapshots.clone().with_update(true).assert("check", "first\n");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "first\n");
+
+    snapshots.assert("check", "first\n");
+    let changed = catch_unwind(|| snapshots.assert("check", "second\n"));
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(changed.is_err());
+}